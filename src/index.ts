@@ -80,6 +80,26 @@ export {
   isWasmStoreAvailable,
   type ColumnSchema,
   type RowUpdate,
+  type RowKey,
+  type StoreOptions,
+  type CoercionPolicy,
+  type CoercionError,
+  type CsvExportOptions,
+  type CsvImportOptions,
+  type CsvImportResult,
+  type CsvLineError,
+  type XlsxExportOptions,
+  type NewColumn,
+  type ColumnInfo,
+  type ColumnArrays,
+  type ColumnSlice,
+  type IntegerColumnSlice,
+  type StringColumnSlice,
+  type TrigramStats,
+  type SearchMode,
+  type GridError,
+  type GridErrorCode,
+  type CellMatch,
   type SortDirection as WasmSortDirection,
 } from './wasm/WasmGridStore';
 
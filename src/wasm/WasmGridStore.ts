@@ -33,39 +33,283 @@ export interface ColumnSchema {
   type: 'string' | 'number' | 'integer';
   primaryKey?: boolean;
   indexed?: boolean; // Include in text search
+  index?: 'hash' | 'sorted'; // Secondary index for findRows (sorted also serves ranges)
+  coercion?: CoercionPolicy; // Overrides the store-wide policy for this column
+  nullable?: boolean; // Declared for consumers (default true), not enforced
+  collation?: 'binary' | 'nocase'; // String sort order (default binary)
+  format?: string; // Display hint, e.g. 'price' or 'timestamp'
+  units?: string;
+  meta?: Record<string, unknown>; // Free-form, returned as-is by getSchema()
+}
+
+/** Column definition as reported by getSchema() - defaults filled in */
+export interface ColumnInfo extends ColumnSchema {
+  primaryKey: boolean;
+  indexed: boolean;
+  nullable: boolean;
+  collation: 'binary' | 'nocase';
+  coercion: CoercionPolicy;
+  /** Position within a composite primary key */
+  keyPosition?: number;
+}
+
+/** Column added to a live store - existing rows get `default` (or null) */
+export interface NewColumn extends Omit<ColumnSchema, 'primaryKey'> {
+  default?: unknown;
+}
+
+/**
+ * How wrongly typed values are handled:
+ * - 'legacy': stored as null (default)
+ * - 'strict': rejected - the cell keeps its value (null on insert)
+ * - 'lenient': strings parsed as numbers ("1,234.5", "1.2k"), numbers formatted as strings
+ * Integer columns hold 32-bit integers: strict rejects fractions and out-of-range values,
+ * lenient rounds fractions (and logs them). An integer primary key outside the 32-bit
+ * range is rejected with INVALID_KEY under both
+ * Failures are logged under every policy - see takeCoercionErrors()
+ */
+export type CoercionPolicy = 'legacy' | 'strict' | 'lenient';
+
+/** A value that failed coercion */
+export interface CoercionError {
+  key: RowKey;
+  column: string;
+  value: unknown;
+  expected: 'number' | 'integer' | 'string';
+  policy: CoercionPolicy;
 }
 
 export interface RowUpdate {
-  id: string;
+  id?: string | number; // Or whatever the key columns are named
   [field: string]: unknown;
 }
 
+export interface StoreOptions {
+  /** Composite primary key, e.g. ['account', 'symbol'] */
+  primaryKey?: string[];
+  /** Store-wide coercion policy (default 'legacy') */
+  coercion?: CoercionPolicy;
+}
+
+/** A primary key: the value, parts of a composite key, or an object with the key columns */
+export type RowKey = string | number | (string | number)[] | Record<string, unknown>;
+
+export interface CsvImportOptions {
+  /** Single ASCII character (default ',') - '\t' for TSV */
+  delimiter?: string;
+  /** Single ASCII character (default '"') */
+  quote?: string;
+  /** First line names the columns, matched to the schema by name (default true) */
+  header?: boolean;
+  /** Column names in file order when there is no header (default: schema order) */
+  columns?: string[];
+  /** Header -> column renames, e.g. { 'Last Px': 'price' } */
+  mapping?: Record<string, string>;
+  /**
+   * Accept formatted numbers like "1,234.5" or "1.2k" in every number column
+   * (columns with lenient coercion always do). Schema columns keep their declared types.
+   */
+  lenientNumbers?: boolean;
+  /**
+   * Add headers that match no schema column as new columns, typed from their cells:
+   * integer if every non-empty cell is a 32-bit integer, number if every one parses,
+   * else string. Existing rows read null there. Needs a header row (default false)
+   */
+  inferTypes?: boolean;
+}
+
+/** A CSV line that was skipped, or loaded with a null cell (`column` set) */
+export interface CsvLineError {
+  line: number;
+  column?: string;
+  message: string;
+}
+
+export interface CsvImportResult {
+  rows: number;
+  errors: CsvLineError[];
+  /** Errors beyond the first 1000, counted but not listed */
+  dropped: number;
+  /** Columns added by inferTypes, in file order */
+  addedColumns: { name: string; type: ColumnSchema['type'] }[];
+}
+
+export interface CsvExportOptions {
+  /** Row indices to export (e.g. a selection) instead of the current view */
+  rows?: Uint32Array | number[];
+  /** Columns to export, in order (default: all) */
+  columns?: string[];
+  /** Column delimiter (default ',') */
+  delimiter?: string;
+  /** Include the header line (default true) */
+  includeHeaders?: boolean;
+  /** Header text per column name (default: the column name) */
+  headers?: Record<string, string>;
+  /** Slice of the rows to export, for chunking */
+  start?: number;
+  count?: number;
+  /** These rows follow an earlier chunk: no header, output starts with the line break */
+  continuation?: boolean;
+}
+
+/**
+ * Timestamps: number columns with format 'timestamp', 'datetime' or 'date'
+ * (epoch ms) are written as Excel dates
+ */
+export interface XlsxExportOptions {
+  /** Row indices to export (e.g. a selection) instead of the current view */
+  rows?: Uint32Array | number[];
+  /** Columns to export, in order (default: all) */
+  columns?: string[];
+  /** Include the bold header row (default true) */
+  includeHeaders?: boolean;
+  /** Header text per column name (default: the column name) */
+  headers?: Record<string, string>;
+  /** Worksheet name (default 'Sheet1') */
+  sheetName?: string;
+}
+
+/** Column-major snapshot: { id: [...], price: Float64Array, ... } */
+export type ColumnArrays = Record<string, ArrayLike<unknown>>;
+
 export type SortDirection = 'asc' | 'desc' | null;
 
+/** How filter text matches: exact substrings, typo-tolerant, or a regex */
+export type SearchMode = 'substring' | 'fuzzy' | 'regex';
+
+/** Matched character ranges in one cell - UTF-16 offsets, end exclusive */
+export interface CellMatch {
+  column: string;
+  ranges: [number, number][];
+}
+
+/** Machine-readable codes carried by GridError */
+export type GridErrorCode =
+  | 'INVALID_SCHEMA'
+  | 'UNKNOWN_COLUMN_TYPE'
+  | 'UNKNOWN_INDEX_TYPE'
+  | 'DUPLICATE_COLUMN'
+  | 'MISSING_PRIMARY_KEY'
+  | 'MULTIPLE_PRIMARY_KEYS'
+  | 'PRIMARY_KEY_COLUMN'
+  | 'UNKNOWN_COLUMN'
+  | 'COLUMN_TYPE_MISMATCH'
+  | 'COLUMN_LENGTH_MISMATCH'
+  | 'MISSING_KEY'
+  | 'INVALID_KEY'
+  | 'DUPLICATE_KEY'
+  | 'ROW_NOT_FOUND'
+  | 'INVALID_BINARY'
+  | 'INVALID_SNAPSHOT'
+  | 'UNSUPPORTED_SNAPSHOT_VERSION'
+  | 'INVALID_ARROW'
+  | 'UNSUPPORTED_ARROW_TYPE'
+  | 'INVALID_CSV'
+  | 'TOO_MANY_ROWS'
+  | 'INVALID_REGEX'
+  | 'REGEX_TOO_LARGE';
+
+/** Errors thrown by the WASM store carry a machine-readable code */
+export interface GridError extends Error {
+  name: 'GridError';
+  code: GridErrorCode;
+  /** Offset into the offending input: regex char, binary batch or snapshot byte, or schema entry */
+  position?: number;
+  /** Column the error is about, if any */
+  column?: string;
+}
+
+/** Trigram index size report (see trigramStats) */
+export interface TrigramStats {
+  trigrams: number;
+  shortGrams: number;
+  emptyLists: number;
+  postings: number;
+  estimatedBytes: number;
+  largest: { trigram: string; rows: number }[];
+}
+
+/** Dictionary-encoded string column: values[codes[i]] is the cell for row i */
+export interface StringColumnSlice {
+  codes: Int32Array;
+  values: string[];
+}
+
+/** Integer column: values[i] is the cell for row i unless nulls[i] is 1 (values[i] is then 0) */
+export interface IntegerColumnSlice {
+  values: Int32Array;
+  nulls: Uint8Array;
+}
+
+/**
+ * Columnar slice of the view - one shape per column type:
+ * number -> Float64Array (NaN = null), integer -> IntegerColumnSlice, string -> StringColumnSlice
+ */
+export type ColumnSlice = Float64Array | IntegerColumnSlice | StringColumnSlice;
+
 // WASM module types (internal)
 interface WasmGridStoreInternal {
   loadRows(rows: unknown[]): number;
+  loadRowsBinary(bytes: Uint8Array): number;
+  // Only in builds with the `arrow` cargo feature
+  loadArrow?(bytes: Uint8Array): number;
+  exportArrow?(allRows?: boolean, fileFormat?: boolean): Uint8Array;
+  loadColumns(columns: ColumnArrays | unknown[][]): number;
+  loadCsv(bytes: Uint8Array, options?: CsvImportOptions): CsvImportResult;
+  exportCsv(options?: CsvExportOptions): Uint8Array;
+  exportXlsx(options?: XlsxExportOptions): Uint8Array;
   insert(row: unknown): number;
-  update(id: string, changes: unknown): void;
+  update(key: RowKey, changes: unknown): void;
   batchUpdate(updates: unknown[]): number;
-  delete(id: string): void;
+  batchUpdateBinary(bytes: Uint8Array): number;
+  delete(key: RowKey): void;
   setFilter(search: string): void;
+  setDiacriticFolding(enabled: boolean): void;
+  setSearchMode(mode: number): void;
+  setFuzzyThreshold(threshold: number): void;
+  setRankByRelevance(enabled: boolean): void;
   setSort(column: string, direction: number): void;
+  setRangeFilter(column: string, min?: number, max?: number): void;
+  clearRangeFilter(column?: string): void;
   clearFilter(): void;
   clearSort(): void;
   viewCount(): number;
   rowCount(): number;
   viewIndices(start: number, count: number): Uint32Array;
+  liveRows(rows: Uint32Array | number[]): Uint32Array;
+  viewPtr(): number;
+  viewLen(): number;
+  viewIndicesView(): Uint32Array;
+  numberColumnPtr(column: string): number;
+  numberColumnView(column: string): Float64Array;
+  columnLen(): number;
   getRows(indices: Uint32Array): unknown[];
   getVisibleRows(start: number, count: number): unknown[];
+  getVisibleColumns(start: number, count: number, columns: string[]): Record<string, ColumnSlice>;
+  getVisibleMatches(start: number, count: number): CellMatch[][];
   getCell(row: number, column: string): unknown;
+  findRows(column: string, value: unknown): Uint32Array;
+  findRowsInRange(column: string, min: unknown, max: unknown): Uint32Array;
+  topN(column: string, count: number, direction: number): Uint32Array;
   columnNames(): string[];
+  schema(): ColumnInfo[];
+  addColumn(def: NewColumn): void;
+  dropColumn(name: string): void;
+  setColumnIndexed(name: string, indexed: boolean): void;
+  takeCoercionErrors(): { errors: CoercionError[]; dropped: number };
+  compactTrigramIndex(): number;
+  trigramStats(top?: number): TrigramStats;
+  serialize(includeIndex?: boolean): Uint8Array;
   free(): void;
 }
 
 interface WasmModule {
-  GridStore: new (schema: ColumnSchema[]) => WasmGridStoreInternal;
+  GridStore: {
+    new (schema: ColumnSchema[], options?: StoreOptions): WasmGridStoreInternal;
+    deserialize(bytes: Uint8Array): WasmGridStoreInternal;
+  };
   SortDir: { Asc: 0; Desc: 1; None: 2 };
+  SearchMode: { Substring: 0; Fuzzy: 1; Regex: 2 };
   default(input?: unknown): Promise<unknown>;
 }
 
@@ -115,26 +359,46 @@ export function isWasmStoreAvailable(): boolean {
 export class WasmGridStore<T extends Record<string, unknown> = Record<string, unknown>> {
   private store: WasmGridStoreInternal | null = null;
   private schema: ColumnSchema[];
+  private options: StoreOptions;
   private listeners: Set<() => void> = new Set();
   private _viewCount = 0;
 
-  private constructor(schema: ColumnSchema[]) {
+  private constructor(schema: ColumnSchema[], options: StoreOptions) {
     this.schema = schema;
+    this.options = options;
   }
 
   /**
    * Create a new WasmGridStore
    */
-  static async create<T extends Record<string, unknown>>(schema: ColumnSchema[]): Promise<WasmGridStore<T>> {
-    const instance = new WasmGridStore<T>(schema);
+  static async create<T extends Record<string, unknown>>(
+    schema: ColumnSchema[],
+    options: StoreOptions = {}
+  ): Promise<WasmGridStore<T>> {
+    const instance = new WasmGridStore<T>(schema, options);
     await instance.init();
     return instance;
   }
 
+  /**
+   * Restore a store saved with serialize() - much faster than loadRows
+   * Throws if WASM is unavailable, or a GridError for an incompatible snapshot.
+   */
+  static async fromSnapshot<T extends Record<string, unknown>>(bytes: Uint8Array): Promise<WasmGridStore<T>> {
+    if (!(await initWasmStore()) || !wasmModule) {
+      throw new Error('WASM not available - snapshots need the WASM store');
+    }
+    const store = wasmModule.GridStore.deserialize(bytes);
+    const instance = new WasmGridStore<T>(store.schema(), {});
+    instance.store = store;
+    instance._viewCount = store.viewCount();
+    return instance;
+  }
+
   private async init(): Promise<void> {
     const available = await initWasmStore();
     if (available && wasmModule) {
-      this.store = new wasmModule.GridStore(this.schema);
+      this.store = new wasmModule.GridStore(this.schema, this.options);
     }
   }
 
@@ -158,6 +422,129 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
     return count;
   }
 
+  /**
+   * Load initial data from a binary batch (schema-ordered, little-endian)
+   * See wasm/src/binary.rs for the format
+   */
+  loadRowsBinary(bytes: Uint8Array): number {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    const count = this.store.loadRowsBinary(bytes);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+    return count;
+  }
+
+  /**
+   * Load rows from CSV bytes, parsed in WASM straight into typed columns
+   * Malformed lines are skipped and reported, not thrown
+   */
+  loadCsv(bytes: Uint8Array, options: CsvImportOptions = {}): CsvImportResult {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    const result = this.store.loadCsv(bytes, options);
+    if (result.addedColumns.length > 0) {
+      this.schema = [...this.schema, ...result.addedColumns];
+    }
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+    return result;
+  }
+
+  /**
+   * Export the current view (or options.rows) as UTF-8 CSV bytes
+   * Escaping matches exportToCSV; prepend a BOM if Excel needs one
+   */
+  exportCsv(options: CsvExportOptions = {}): Uint8Array {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    return this.store.exportCsv(options);
+  }
+
+  /**
+   * Export CSV in chunks of `chunkRows` rows - concatenated, the chunks equal
+   * exportCsv(options). Yield to the event loop between chunks for big exports.
+   * The rows are snapshotted up front, so updates or re-sorts between chunks
+   * don't duplicate or skip rows; rows deleted meanwhile are left out.
+   */
+  *exportCsvChunks(options: CsvExportOptions = {}, chunkRows = 100_000): Generator<Uint8Array> {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    const rows = options.rows
+      ? this.store.liveRows(options.rows)
+      : this.store.viewIndices(0, this.store.viewCount());
+    for (let start = 0; start === 0 || start < rows.length; start += chunkRows) {
+      yield this.store.exportCsv({
+        ...options,
+        rows: rows.subarray(start, start + chunkRows),
+        start: undefined,
+        count: undefined,
+        continuation: start > 0,
+      });
+    }
+  }
+
+  /**
+   * Export the current view (or options.rows) as an .xlsx workbook
+   * Numbers and timestamps keep their types; wrap in a Blob to download
+   */
+  exportXlsx(options: XlsxExportOptions = {}): Uint8Array {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    return this.store.exportXlsx(options);
+  }
+
+  /**
+   * Load rows from Arrow IPC bytes (stream or file format) - O(n * cols)
+   * Columns are matched by name; needs a WASM build with the `arrow` feature
+   */
+  loadArrow(bytes: Uint8Array): number {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    if (!this.store.loadArrow) {
+      throw new Error('WASM module was built without Arrow support');
+    }
+    const count = this.store.loadArrow(bytes);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+    return count;
+  }
+
+  /**
+   * Export the current view (or every row with allRows) as Arrow IPC
+   * Stream format unless fileFormat; needs a WASM build with the `arrow` feature
+   * Every field is nullable; null numbers and empty strings export as Arrow nulls
+   */
+  exportArrow(options: { allRows?: boolean; fileFormat?: boolean } = {}): Uint8Array {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    if (!this.store.exportArrow) {
+      throw new Error('WASM module was built without Arrow support');
+    }
+    return this.store.exportArrow(options.allRows, options.fileFormat);
+  }
+
+  /**
+   * Load initial data from column arrays - O(n * cols), no row objects
+   * Accepts an object keyed by column name or arrays in schema order
+   */
+  loadColumns(columns: ColumnArrays | unknown[][]): number {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    const count = this.store.loadColumns(columns);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+    return count;
+  }
+
   /**
    * Insert a single row - O(cols)
    */
@@ -186,19 +573,45 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
   }
 
   /**
-   * Delete a row by ID
+   * Update rows from a binary batch - each row must carry the primary key
+   */
+  updateRowsBinary(bytes: Uint8Array): number {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    const count = this.store.batchUpdateBinary(bytes);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+    return count;
+  }
+
+  /**
+   * Update one row by key - key columns in `changes` are ignored
    */
-  deleteRow(id: string): void {
+  updateRow(key: RowKey, changes: Partial<T>): void {
     if (!this.store) {
       throw new Error('WasmGridStore not initialized');
     }
-    this.store.delete(id);
+    this.store.update(key, changes);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Delete a row by key (value, composite parts array, or object)
+   */
+  deleteRow(key: RowKey): void {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    this.store.delete(key);
     this._viewCount = this.store.viewCount();
     this.notifyListeners();
   }
 
   /**
    * Set filter text
+   * In regex mode an invalid pattern throws a GridError (code INVALID_REGEX)
    */
   setFilter(search: string): void {
     if (!this.store) return;
@@ -207,6 +620,49 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
     this.notifyListeners();
   }
 
+  /**
+   * Fold diacritics in search ("societe" matches "Société")
+   * Rebuilds the search index
+   */
+  setDiacriticFolding(enabled: boolean): void {
+    if (!this.store) return;
+    this.store.setDiacriticFolding(enabled);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Set search mode - 'fuzzy' tolerates typos ("APPL" finds "AAPL"),
+   * 'regex' treats the filter text as a pattern (throws GridError if invalid)
+   */
+  setSearchMode(mode: SearchMode): void {
+    if (!this.store || !wasmModule) return;
+    const modes = wasmModule.SearchMode;
+    this.store.setSearchMode(mode === 'fuzzy' ? modes.Fuzzy : mode === 'regex' ? modes.Regex : modes.Substring);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Minimum fuzzy match score, 0..1 (default 0.5)
+   */
+  setFuzzyThreshold(threshold: number): void {
+    if (!this.store) return;
+    this.store.setFuzzyThreshold(threshold);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * In fuzzy mode, order rows by match quality instead of the sort column
+   */
+  setRankByRelevance(enabled: boolean): void {
+    if (!this.store) return;
+    this.store.setRankByRelevance(enabled);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
   /**
    * Set sort column and direction
    */
@@ -223,6 +679,28 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
     this.notifyListeners();
   }
 
+  /**
+   * Restrict the view to min <= column <= max on a numeric column
+   * Omit a bound to leave that side open; ANDed with the text filter
+   */
+  setRangeFilter(column: string, min?: number, max?: number): void {
+    if (!this.store) return;
+    this.store.setRangeFilter(column, min, max);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Remove the range filter on a column (all range filters if omitted)
+   * Throws a GridError (code UNKNOWN_COLUMN) for a column not in the schema
+   */
+  clearRangeFilter(column?: string): void {
+    if (!this.store) return;
+    this.store.clearRangeFilter(column);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
   /**
    * Clear filter
    */
@@ -267,6 +745,42 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
     return this.store.getVisibleRows(start, count) as T[];
   }
 
+  /**
+   * Get filter match ranges for visible rows (for highlighting)
+   * One array per visible row, aligned with getVisibleRows(start, count)
+   */
+  getVisibleMatches(start: number, count: number): CellMatch[][] {
+    if (!this.store) return [];
+    return this.store.getVisibleMatches(start, count);
+  }
+
+  /**
+   * Get visible rows in columnar form - one typed array per column
+   * Cheaper than getVisibleRows for wide grids (no per-row objects)
+   */
+  getVisibleColumns(start: number, count: number, columns: string[]): Record<string, ColumnSlice> {
+    if (!this.store) return {};
+    return this.store.getVisibleColumns(start, count, columns);
+  }
+
+  /**
+   * Zero-copy view over all view indices in WASM memory
+   * Only valid until the next call into the store - read it immediately.
+   */
+  getViewIndicesView(): Uint32Array {
+    if (!this.store) return new Uint32Array(0);
+    return this.store.viewIndicesView();
+  }
+
+  /**
+   * Zero-copy view over a numeric column, indexed by row (NaN = null)
+   * Only valid until the next call into the store - read it immediately.
+   */
+  getNumberColumnView(column: string): Float64Array {
+    if (!this.store) return new Float64Array(0);
+    return this.store.numberColumnView(column);
+  }
+
   /**
    * Get a single cell value
    */
@@ -275,6 +789,33 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
     return this.store.getCell(rowIndex, column);
   }
 
+  /**
+   * Rows where column equals value - uses the column's secondary index if declared
+   */
+  findRows(column: string, value: unknown): T[] {
+    if (!this.store) return [];
+    return this.store.getRows(this.store.findRows(column, value)) as T[];
+  }
+
+  /**
+   * Rows where min <= column <= max (null/undefined = open end)
+   * Served by a 'sorted' secondary index if declared, else a scan
+   */
+  findRowsInRange(column: string, min: unknown, max: unknown): T[] {
+    if (!this.store) return [];
+    return this.store.getRows(this.store.findRowsInRange(column, min, max)) as T[];
+  }
+
+  /**
+   * Top rows by a column's value (ignores filters, skips nulls and empty strings)
+   * Served by a sorted index on numeric columns - no full sort
+   */
+  getTopN(column: string, count: number, direction: 'asc' | 'desc' = 'desc'): T[] {
+    if (!this.store || !wasmModule) return [];
+    const dir = direction === 'asc' ? wasmModule.SortDir.Asc : wasmModule.SortDir.Desc;
+    return this.store.getRows(this.store.topN(column, count, dir)) as T[];
+  }
+
   /**
    * Get column names
    */
@@ -283,6 +824,89 @@ export class WasmGridStore<T extends Record<string, unknown> = Record<string, un
     return this.store.columnNames();
   }
 
+  /**
+   * Full column definitions: type, key, indexing, nullability, collation and metadata
+   */
+  getSchema(): ColumnInfo[] {
+    if (!this.store) return [];
+    return this.store.schema();
+  }
+
+  /**
+   * Add a column to the live store - existing rows are backfilled with def.default or null
+   */
+  addColumn(def: NewColumn): void {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    this.store.addColumn(def);
+    this.schema = [...this.schema, def];
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Drop a column and its data (primary key columns can't be dropped)
+   */
+  dropColumn(name: string): void {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    this.store.dropColumn(name);
+    this.schema = this.schema.filter((c) => c.name !== name);
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Include a column in (or exclude it from) text search
+   */
+  setColumnIndexed(name: string, indexed: boolean): void {
+    if (!this.store) return;
+    this.store.setColumnIndexed(name, indexed);
+    this.schema = this.schema.map((c) => (c.name === name ? { ...c, indexed } : c));
+    this._viewCount = this.store.viewCount();
+    this.notifyListeners();
+  }
+
+  /**
+   * Drain values that failed type coercion since the last call
+   * `dropped` counts failures beyond the log's capacity (10k)
+   */
+  takeCoercionErrors(): { errors: CoercionError[]; dropped: number } {
+    if (!this.store) return { errors: [], dropped: 0 };
+    return this.store.takeCoercionErrors();
+  }
+
+  /**
+   * Save the rows and schema as a versioned binary snapshot for fromSnapshot()
+   * includeIndex also saves the text search index: a larger snapshot, a faster restore.
+   * Filter and sort state are not saved.
+   */
+  serialize(options: { includeIndex?: boolean } = {}): Uint8Array {
+    if (!this.store) {
+      throw new Error('WasmGridStore not initialized');
+    }
+    return this.store.serialize(options.includeIndex);
+  }
+
+  /**
+   * Drop empty trigram posting lists left behind by churn
+   * Returns number of lists removed
+   */
+  compactTrigramIndex(): number {
+    if (!this.store) return 0;
+    return this.store.compactTrigramIndex();
+  }
+
+  /**
+   * Trigram index statistics (list counts, postings, estimated bytes)
+   */
+  getTrigramStats(top?: number): TrigramStats | null {
+    if (!this.store) return null;
+    return this.store.trigramStats(top);
+  }
+
   /**
    * Subscribe to view changes
    */
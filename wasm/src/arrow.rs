@@ -0,0 +1,259 @@
+//! Apache Arrow IPC import/export (cargo feature `arrow`)
+//!
+//! Type mapping, Arrow -> store:
+//! - string columns:  Utf8, LargeUtf8, Utf8View, or a dictionary of those
+//! - number/integer:  any int/uint/float, Timestamp and Date (as epoch ms)
+//!
+//! Store -> Arrow: string -> Utf8, number -> Float64, integer -> Int64, with
+//! the schema's `format` / `units` carried as field metadata.
+//!
+//! Arrow nulls load as the store's nulls (NaN / "") and export back as Arrow
+//! nulls, so every exported field is nullable - `nullable: false` in the
+//! schema is declarative only. 64-bit integers beyond 2^53 lose precision,
+//! as they would in JS.
+
+use crate::error::{ErrorCode, GridError};
+use crate::{ColumnData, ColumnType, GridStore};
+use arrow_array::cast::AsArray;
+use arrow_array::types::*;
+use arrow_array::{
+    Array, ArrayRef, ArrowPrimitiveType, Float64Array, Int64Array, RecordBatch, RecordBatchReader, StringArray,
+};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+
+/// Leading bytes of the IPC file format - anything else is read as a stream
+const FILE_MAGIC: &[u8] = b"ARROW1";
+/// Rows per exported record batch
+const EXPORT_BATCH_ROWS: usize = 64 * 1024;
+
+impl From<ArrowError> for GridError {
+    fn from(err: ArrowError) -> GridError {
+        GridError::new(ErrorCode::InvalidArrow, format!("Invalid Arrow IPC data: {}", err))
+    }
+}
+
+#[wasm_bindgen]
+impl GridStore {
+    /// Load rows from Arrow IPC bytes, stream or file format - O(n * cols)
+    /// Columns are matched to the schema by name: missing ones load as null,
+    /// extra ones are ignored. All batches load together or not at all.
+    /// Integer cells outside the coercion policy are nulled (rounded, for
+    /// lenient) and logged, as in loadColumns.
+    /// Returns number of rows loaded
+    #[wasm_bindgen(js_name = loadArrow)]
+    pub fn load_arrow(&mut self, bytes: &[u8]) -> Result<u32, GridError> {
+        let reader: Box<dyn RecordBatchReader> = if bytes.starts_with(FILE_MAGIC) {
+            Box::new(FileReader::try_new(Cursor::new(bytes), None)?)
+        } else {
+            Box::new(StreamReader::try_new(Cursor::new(bytes), None)?)
+        };
+        check_schema(&self.columns_by_name(), &reader.schema())?;
+        let batches: Vec<RecordBatch> = reader.collect::<Result<_, _>>()?;
+
+        let mut loaded: Vec<Option<ColumnData>> = vec![None; self.columns.len()];
+        let mut count = 0;
+        for batch in &batches {
+            for (col_idx, col) in self.columns.iter().enumerate() {
+                let Some(array) = batch.column_by_name(&col.name) else {
+                    // Absent here but maybe present in another batch - pad with nulls
+                    if let Some(data) = &mut loaded[col_idx] {
+                        (0..batch.num_rows()).for_each(|_| data.push_null());
+                    }
+                    continue;
+                };
+                let data = loaded[col_idx].get_or_insert_with(|| col.data.nulls_like(count));
+                append_array(data, array).map_err(|e| e.on_column(col.name.clone()))?;
+            }
+            count += batch.num_rows();
+        }
+
+        // Integer columns get the policy's 32-bit check; key columns are
+        // left to key validation so a bad key fails as INVALID_KEY
+        let mut failures = Vec::new();
+        let mut column_failures = Vec::new();
+        for (col_idx, data) in loaded.iter_mut().enumerate() {
+            let col = &self.columns[col_idx];
+            if let (Some(data), ColumnType::Integer) = (data, col.col_type) {
+                if !self.key_columns.contains(&col_idx) {
+                    data.check_integers(col.coercion, &mut column_failures);
+                    failures.extend(column_failures.drain(..).map(|(row, value)| (col_idx, row, value)));
+                }
+            }
+        }
+
+        self.append_columns(loaded, count, failures)
+    }
+
+    /// Export as Arrow IPC - the current view by default, or every live row
+    /// in insertion order with `allRows`. Stream format unless `fileFormat`.
+    #[wasm_bindgen(js_name = exportArrow)]
+    pub fn export_arrow(&mut self, all_rows: Option<bool>, file_format: Option<bool>) -> Result<Vec<u8>, GridError> {
+        let rows: Vec<u32> = if all_rows.unwrap_or(false) {
+            (0..self.row_count as u32).filter(|&row| !self.deleted[row as usize]).collect()
+        } else {
+            self.ensure_view();
+            self.view.cached_view.clone().unwrap_or_default()
+        };
+
+        let fields: Vec<Field> = self.columns
+            .iter()
+            .map(|col| {
+                let data_type = match col.col_type {
+                    ColumnType::String => DataType::Utf8,
+                    ColumnType::Number => DataType::Float64,
+                    ColumnType::Integer => DataType::Int64,
+                };
+                let metadata: HashMap<String, String> = [("format", &col.format), ("units", &col.units)]
+                    .into_iter()
+                    .filter_map(|(key, value)| value.clone().map(|v| (key.to_string(), v)))
+                    .collect();
+                Field::new(col.name.clone(), data_type, true).with_metadata(metadata)
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+
+        let batches = rows.chunks(EXPORT_BATCH_ROWS).map(|chunk| {
+            let arrays: Vec<ArrayRef> = self.columns.iter().map(|col| gather_array(col, chunk)).collect();
+            RecordBatch::try_new(schema.clone(), arrays)
+        });
+
+        if file_format.unwrap_or(false) {
+            let mut writer = FileWriter::try_new(Vec::new(), &schema)?;
+            for batch in batches {
+                writer.write(&batch?)?;
+            }
+            writer.finish()?;
+            Ok(writer.into_inner()?)
+        } else {
+            let mut writer = StreamWriter::try_new(Vec::new(), &schema)?;
+            for batch in batches {
+                writer.write(&batch?)?;
+            }
+            writer.finish()?;
+            Ok(writer.into_inner()?)
+        }
+    }
+}
+
+impl GridStore {
+    fn columns_by_name(&self) -> HashMap<&str, &ColumnData> {
+        self.columns.iter().map(|col| (col.name.as_str(), &col.data)).collect()
+    }
+}
+
+/// Reject unmappable types up front, before any batch is decoded
+fn check_schema(columns: &HashMap<&str, &ColumnData>, schema: &Schema) -> Result<(), GridError> {
+    for field in schema.fields() {
+        if let Some(data) = columns.get(field.name().as_str()) {
+            let supported = match data {
+                ColumnData::Strings(_) => is_string_type(field.data_type()),
+                ColumnData::Numbers(_) => is_number_type(field.data_type()),
+            };
+            if !supported {
+                return Err(type_error(data, field.data_type()).on_column(field.name().clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_string_type(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => true,
+        DataType::Dictionary(_, values) => is_string_type(values),
+        _ => false,
+    }
+}
+
+fn is_number_type(data_type: &DataType) -> bool {
+    data_type.is_numeric() && !matches!(data_type, DataType::Decimal128(..) | DataType::Decimal256(..))
+        || matches!(data_type, DataType::Timestamp(..) | DataType::Date32 | DataType::Date64)
+}
+
+fn type_error(data: &ColumnData, data_type: &DataType) -> GridError {
+    let (code, expected) = match data {
+        ColumnData::Strings(_) if is_number_type(data_type) => (ErrorCode::ColumnTypeMismatch, "string"),
+        ColumnData::Numbers(_) if is_string_type(data_type) => (ErrorCode::ColumnTypeMismatch, "number"),
+        ColumnData::Strings(_) => (ErrorCode::UnsupportedArrowType, "string"),
+        ColumnData::Numbers(_) => (ErrorCode::UnsupportedArrowType, "number"),
+    };
+    GridError::new(code, format!("Arrow type {} can't be loaded into a {} column", data_type, expected))
+}
+
+/// Append one Arrow array to a column
+fn append_array(data: &mut ColumnData, array: &ArrayRef) -> Result<(), GridError> {
+    match data {
+        ColumnData::Strings(v) => append_strings(v, array).ok_or_else(|| type_error(data, array.data_type())),
+        ColumnData::Numbers(v) => append_numbers(v, array).ok_or_else(|| type_error(data, array.data_type())),
+    }
+}
+
+fn append_strings(out: &mut Vec<String>, array: &ArrayRef) -> Option<()> {
+    let cell = |s: Option<&str>| s.unwrap_or_default().to_string();
+    match array.data_type() {
+        DataType::Utf8 => out.extend(array.as_string::<i32>().iter().map(cell)),
+        DataType::LargeUtf8 => out.extend(array.as_string::<i64>().iter().map(cell)),
+        DataType::Utf8View => out.extend(array.as_string_view().iter().map(cell)),
+        DataType::Dictionary(..) => {
+            let dict = array.as_any_dictionary();
+            let mut values = Vec::new();
+            append_strings(&mut values, dict.values())?;
+            out.extend(dict.normalized_keys().into_iter().enumerate().map(|(i, key)| {
+                if array.is_null(i) { String::new() } else { values[key].clone() }
+            }));
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+fn append_numbers(out: &mut Vec<f64>, array: &ArrayRef) -> Option<()> {
+    fn push<T: ArrowPrimitiveType>(out: &mut Vec<f64>, array: &ArrayRef, scale: f64, to_f64: impl Fn(T::Native) -> f64) {
+        out.extend(array.as_primitive::<T>().iter().map(|n| n.map_or(f64::NAN, |n| to_f64(n) * scale)));
+    }
+
+    const DAY_MS: f64 = 86_400_000.0;
+    match array.data_type() {
+        DataType::Int8 => push::<Int8Type>(out, array, 1.0, f64::from),
+        DataType::Int16 => push::<Int16Type>(out, array, 1.0, f64::from),
+        DataType::Int32 => push::<Int32Type>(out, array, 1.0, f64::from),
+        DataType::Int64 => push::<Int64Type>(out, array, 1.0, |n| n as f64),
+        DataType::UInt8 => push::<UInt8Type>(out, array, 1.0, f64::from),
+        DataType::UInt16 => push::<UInt16Type>(out, array, 1.0, f64::from),
+        DataType::UInt32 => push::<UInt32Type>(out, array, 1.0, f64::from),
+        DataType::UInt64 => push::<UInt64Type>(out, array, 1.0, |n| n as f64),
+        DataType::Float16 => push::<Float16Type>(out, array, 1.0, |n| n.to_f64()),
+        DataType::Float32 => push::<Float32Type>(out, array, 1.0, f64::from),
+        DataType::Float64 => push::<Float64Type>(out, array, 1.0, |n| n),
+        DataType::Timestamp(TimeUnit::Second, _) => push::<TimestampSecondType>(out, array, 1e3, |n| n as f64),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => push::<TimestampMillisecondType>(out, array, 1.0, |n| n as f64),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => push::<TimestampMicrosecondType>(out, array, 1e-3, |n| n as f64),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => push::<TimestampNanosecondType>(out, array, 1e-6, |n| n as f64),
+        DataType::Date32 => push::<Date32Type>(out, array, DAY_MS, f64::from),
+        DataType::Date64 => push::<Date64Type>(out, array, 1.0, |n| n as f64),
+        _ => return None,
+    }
+    Some(())
+}
+
+/// One column's cells for the given rows as an Arrow array
+fn gather_array(col: &crate::Column, rows: &[u32]) -> ArrayRef {
+    match (&col.data, col.col_type) {
+        // "" is the store's string null
+        (ColumnData::Strings(v), _) => Arc::new(
+            rows.iter().map(|&r| Some(v[r as usize].as_str()).filter(|s| !s.is_empty())).collect::<StringArray>(),
+        ),
+        (ColumnData::Numbers(v), ColumnType::Integer) => Arc::new(
+            rows.iter().map(|&r| Some(v[r as usize]).filter(|n| !n.is_nan()).map(|n| n as i64)).collect::<Int64Array>(),
+        ),
+        (ColumnData::Numbers(v), _) => Arc::new(
+            rows.iter().map(|&r| Some(v[r as usize]).filter(|n| !n.is_nan())).collect::<Float64Array>(),
+        ),
+    }
+}
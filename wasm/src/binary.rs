@@ -0,0 +1,302 @@
+//! Compact binary row format for `loadRowsBinary` / `batchUpdateBinary`
+//!
+//! Walking JS objects with `Reflect::get` per field is the bottleneck for
+//! high-frequency feeds. This format lets a feed handler hand the store one
+//! `Uint8Array` per batch instead.
+//!
+//! Layout (all integers/floats little-endian):
+//!
+//! ```text
+//! batch  := u32 row_count, row * row_count
+//! row    := presence bitmap (ceil(column_count / 8) bytes, bit i = column i)
+//!           value for each present column, in schema order
+//! value  := number -> f64
+//!           string -> u32 byte_length, UTF-8 bytes
+//! ```
+//!
+//! Absent columns are left untouched on update and stored as null on insert.
+//! The primary key column must be present in every row.
+
+use std::fmt;
+
+/// Value kind of a schema column, in schema order
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ValueKind {
+    Number,
+    String,
+}
+
+/// A single encoded cell
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Value<'a> {
+    Number(f64),
+    String(&'a str),
+}
+
+#[derive(Debug)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid binary batch at byte {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+// ============================================================================
+// Encoder (for Rust feed handlers)
+// ============================================================================
+
+/// Builds a binary batch for a store with the given column kinds
+///
+/// ```
+/// use askturret_grid_wasm::binary::{BatchEncoder, Value, ValueKind};
+///
+/// let mut enc = BatchEncoder::new(&[ValueKind::String, ValueKind::Number]);
+/// enc.push_row(&[Some(Value::String("row1")), Some(Value::Number(150.5))]).unwrap();
+/// enc.push_row(&[Some(Value::String("row2")), None]).unwrap();
+/// let bytes = enc.finish();
+/// assert_eq!(&bytes[..4], &2u32.to_le_bytes());
+/// ```
+pub struct BatchEncoder {
+    kinds: Vec<ValueKind>,
+    buf: Vec<u8>,
+    rows: u32,
+}
+
+impl BatchEncoder {
+    pub fn new(kinds: &[ValueKind]) -> Self {
+        Self {
+            kinds: kinds.to_vec(),
+            buf: vec![0; 4],
+            rows: 0,
+        }
+    }
+
+    /// Append one row - `values[i]` is column i, `None` = not present
+    pub fn push_row(&mut self, values: &[Option<Value>]) -> Result<(), String> {
+        if values.len() != self.kinds.len() {
+            return Err(format!(
+                "Expected {} values, got {}",
+                self.kinds.len(),
+                values.len()
+            ));
+        }
+
+        let bitmap_start = self.buf.len();
+        self.buf.resize(bitmap_start + bitmap_len(self.kinds.len()), 0);
+
+        for (i, value) in values.iter().enumerate() {
+            let Some(value) = value else { continue };
+            match (self.kinds[i], value) {
+                (ValueKind::Number, Value::Number(n)) => {
+                    self.buf.extend_from_slice(&n.to_le_bytes());
+                }
+                (ValueKind::String, Value::String(s)) => {
+                    self.buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                    self.buf.extend_from_slice(s.as_bytes());
+                }
+                (kind, _) => {
+                    self.buf.truncate(bitmap_start);
+                    return Err(format!("Column {} expects {:?}", i, kind));
+                }
+            }
+            self.buf[bitmap_start + i / 8] |= 1 << (i % 8);
+        }
+
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn row_count(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.buf[..4].copy_from_slice(&self.rows.to_le_bytes());
+        self.buf
+    }
+}
+
+// ============================================================================
+// Decoder
+// ============================================================================
+
+/// Streaming decoder - yields one row at a time into a reusable buffer
+pub(crate) struct BatchDecoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    kinds: &'a [ValueKind],
+    remaining: u32,
+}
+
+impl<'a> BatchDecoder<'a> {
+    pub(crate) fn new(bytes: &'a [u8], kinds: &'a [ValueKind]) -> Result<Self, DecodeError> {
+        let mut decoder = Self {
+            bytes,
+            pos: 0,
+            kinds,
+            remaining: 0,
+        };
+        decoder.remaining = decoder.read_u32()?;
+        Ok(decoder)
+    }
+
+    /// Rows worth reserving for - the header count, capped by how many rows
+    /// the remaining bytes could hold, so a corrupt header can't force a huge allocation
+    pub(crate) fn capacity_hint(&self) -> usize {
+        let min_row_len = bitmap_len(self.kinds.len()).max(1);
+        (self.remaining as usize).min((self.bytes.len() - self.pos) / min_row_len)
+    }
+
+    /// Decode the next row into `out` - returns false once the batch is exhausted
+    pub(crate) fn next_row(&mut self, out: &mut Vec<Option<Value<'a>>>) -> Result<bool, DecodeError> {
+        if self.remaining == 0 {
+            if self.pos != self.bytes.len() {
+                return Err(self.error("trailing bytes after last row"));
+            }
+            return Ok(false);
+        }
+        self.remaining -= 1;
+
+        let bitmap = self.take(bitmap_len(self.kinds.len()))?;
+        out.clear();
+
+        for (i, kind) in self.kinds.iter().enumerate() {
+            if bitmap[i / 8] & (1 << (i % 8)) == 0 {
+                out.push(None);
+                continue;
+            }
+            let value = match kind {
+                ValueKind::Number => {
+                    let raw = self.take(8)?;
+                    Value::Number(f64::from_le_bytes(raw.try_into().unwrap()))
+                }
+                ValueKind::String => {
+                    let len = self.read_u32()? as usize;
+                    let start = self.pos;
+                    let raw = self.take(len)?;
+                    let s = std::str::from_utf8(raw).map_err(|_| DecodeError {
+                        offset: start,
+                        message: format!("column {} is not valid UTF-8", i),
+                    })?;
+                    Value::String(s)
+                }
+            };
+            out.push(Some(value));
+        }
+
+        Ok(true)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        let raw = self.take(4)?;
+        Ok(u32::from_le_bytes(raw.try_into().unwrap()))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn error(&self, message: &str) -> DecodeError {
+        DecodeError {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn bitmap_len(column_count: usize) -> usize {
+    column_count.div_ceil(8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [ValueKind; 2] = [ValueKind::String, ValueKind::Number];
+
+    fn encode(rows: &[[Option<Value>; 2]]) -> Vec<u8> {
+        let mut enc = BatchEncoder::new(&KINDS);
+        for row in rows {
+            enc.push_row(row).unwrap();
+        }
+        enc.finish()
+    }
+
+    fn decode_all(bytes: &[u8]) -> Result<Vec<Vec<Option<Value<'_>>>>, DecodeError> {
+        let mut decoder = BatchDecoder::new(bytes, &KINDS)?;
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        while decoder.next_row(&mut cells)? {
+            rows.push(cells.clone());
+        }
+        Ok(rows)
+    }
+
+    #[test]
+    fn round_trips_present_and_absent_cells() {
+        let bytes = encode(&[
+            [Some(Value::String("a")), Some(Value::Number(1.5))],
+            [Some(Value::String("b")), None],
+        ]);
+        let rows = decode_all(&bytes).unwrap();
+        assert_eq!(rows, vec![
+            vec![Some(Value::String("a")), Some(Value::Number(1.5))],
+            vec![Some(Value::String("b")), None],
+        ]);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = encode(&[[Some(Value::String("abc")), Some(Value::Number(2.0))]]);
+        for len in 0..bytes.len() {
+            assert!(decode_all(&bytes[..len]).is_err(), "accepted {} of {} bytes", len, bytes.len());
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = encode(&[[Some(Value::String("a")), None]]);
+        bytes.push(0);
+        let err = decode_all(&bytes).unwrap_err();
+        assert_eq!(err.offset, bytes.len() - 1);
+    }
+
+    #[test]
+    fn rejects_oversized_string_length() {
+        let mut bytes = 1u32.to_le_bytes().to_vec();
+        bytes.push(0b01);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_all(&bytes).is_err());
+    }
+
+    #[test]
+    fn caps_capacity_hint_by_remaining_bytes() {
+        let bytes = u32::MAX.to_le_bytes();
+        let decoder = BatchDecoder::new(&bytes, &KINDS).unwrap();
+        assert_eq!(decoder.capacity_hint(), 0);
+
+        let bytes = encode(&[[Some(Value::String("a")), None], [None, None]]);
+        let decoder = BatchDecoder::new(&bytes, &KINDS).unwrap();
+        assert_eq!(decoder.capacity_hint(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let mut bytes = 1u32.to_le_bytes().to_vec();
+        bytes.push(0b01);
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&[0xff, 0xfe]);
+        let err = decode_all(&bytes).unwrap_err();
+        assert_eq!(err.offset, 9);
+    }
+}
@@ -0,0 +1,281 @@
+//! Type coercion of incoming JS values
+//!
+//! Policy per store (`options.coercion`) or per column (schema `coercion`):
+//! - legacy:  wrong types become null (NaN / "") - the historical behavior
+//! - strict:  wrong types are rejected; the cell keeps its value (null on insert)
+//! - lenient: strings are parsed into numbers ("1,234.5", "1.2k") and numbers
+//!   or booleans are formatted into strings; what still fails is rejected
+//!
+//! Integer columns hold 32-bit integers (they cross to JS as Int32Array).
+//! Strict rejects fractions and out-of-range values; lenient rounds fractions
+//! to the nearest integer and logs them; legacy stores numbers as given.
+//!
+//! Every failure, under any policy, is logged with the row key and column and
+//! can be drained with `takeCoercionErrors()`.
+
+use crate::key::RowKey;
+use crate::ColumnType;
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// Failures kept between drains - older ones are counted, not stored
+const MAX_LOGGED: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub(crate) enum CoercionPolicy {
+    #[default]
+    Legacy,
+    Strict,
+    Lenient,
+}
+
+impl CoercionPolicy {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "legacy" => Some(CoercionPolicy::Legacy),
+            "strict" => Some(CoercionPolicy::Strict),
+            "lenient" => Some(CoercionPolicy::Lenient),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            CoercionPolicy::Legacy => "legacy",
+            CoercionPolicy::Strict => "strict",
+            CoercionPolicy::Lenient => "lenient",
+        }
+    }
+
+    /// Whether a failed cell is written as null (legacy) or left alone
+    pub(crate) fn nulls_on_failure(&self) -> bool {
+        *self == CoercionPolicy::Legacy
+    }
+
+    /// Number cell for a column of `col_type` from a JS value - Ok(NaN) for null/undefined
+    pub(crate) fn number(&self, value: &JsValue, col_type: ColumnType) -> Result<f64, CellError> {
+        let n = if let Some(n) = value.as_f64() {
+            n
+        } else if value.is_undefined() || value.is_null() {
+            f64::NAN
+        } else {
+            match (self, value.as_string()) {
+                (CoercionPolicy::Lenient, Some(s)) => parse_number(&s).ok_or(CellError::Rejected)?,
+                _ => return Err(CellError::Rejected),
+            }
+        };
+        match col_type {
+            ColumnType::Integer => self.integer(n),
+            _ => Ok(n),
+        }
+    }
+
+    /// Check a number against an integer column (NaN = null passes)
+    pub(crate) fn integer(&self, n: f64) -> Result<f64, CellError> {
+        if n.is_nan() || *self == CoercionPolicy::Legacy {
+            return Ok(n);
+        }
+        let rounded = n.round();
+        if !(i32::MIN as f64..=i32::MAX as f64).contains(&rounded) {
+            return Err(CellError::Rejected);
+        }
+        match self {
+            _ if rounded == n => Ok(n),
+            CoercionPolicy::Lenient => Err(CellError::Rounded(rounded)),
+            _ => Err(CellError::Rejected),
+        }
+    }
+
+    /// String cell from a JS value - Ok("") for null/undefined
+    pub(crate) fn string(&self, value: &JsValue) -> Result<String, ()> {
+        if let Some(s) = value.as_string() {
+            return Ok(s);
+        }
+        if value.is_undefined() || value.is_null() {
+            return Ok(String::new());
+        }
+        if *self != CoercionPolicy::Lenient {
+            return Err(());
+        }
+        match (value.as_f64(), value.as_bool()) {
+            (Some(n), _) if n.is_finite() => Ok(n.to_string()),
+            (_, Some(b)) => Ok(b.to_string()),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Why a number cell was logged
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum CellError {
+    /// Not coercible - the cell keeps its value (null under legacy / on insert)
+    Rejected,
+    /// Lenient rounding into an integer column - the rounded value is written
+    Rounded(f64),
+}
+
+/// Parse a human-formatted number: "1,234.5", "-42", "1.2k", "3.5M", " 7 "
+/// Empty strings are null. Suffixes: k = 1e3, m = 1e6, b = 1e9, t = 1e12.
+pub(crate) fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(f64::NAN);
+    }
+
+    let (digits, scale) = match text.as_bytes()[text.len() - 1].to_ascii_lowercase() {
+        b'k' => (&text[..text.len() - 1], 1e3),
+        b'm' => (&text[..text.len() - 1], 1e6),
+        b'b' => (&text[..text.len() - 1], 1e9),
+        b't' => (&text[..text.len() - 1], 1e12),
+        _ => (text, 1.0),
+    };
+    let digits = digits.trim_end();
+
+    // Only plain decimal notation - f64::from_str would also take "inf"/"NaN"
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit() || b"+-.,eE".contains(&b)) {
+        return None;
+    }
+
+    let plain = if digits.contains(',') {
+        strip_thousands(digits)?
+    } else {
+        digits.to_string()
+    };
+    plain.parse::<f64>().ok().map(|n| n * scale)
+}
+
+/// "1,234,567.8" -> "1234567.8"; None unless commas group the integer part by 3
+fn strip_thousands(text: &str) -> Option<String> {
+    let (int_part, frac) = match text.find('.') {
+        Some(dot) => (&text[..dot], &text[dot..]),
+        None => (text, ""),
+    };
+    if frac.contains(',') {
+        return None;
+    }
+
+    let unsigned = int_part.trim_start_matches(['+', '-']);
+    let mut groups = unsigned.split(',');
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 || groups.clone().any(|g| g.len() != 3) {
+        return None;
+    }
+
+    let mut out = String::with_capacity(text.len());
+    out.push_str(&int_part[..int_part.len() - unsigned.len()]);
+    out.extend(unsigned.split(','));
+    out.push_str(frac);
+    Some(out)
+}
+
+// ============================================================================
+// Failure log
+// ============================================================================
+
+pub(crate) struct CoercionError {
+    pub(crate) key: RowKey,
+    pub(crate) column: String,
+    pub(crate) value: JsValue,
+    pub(crate) expected: &'static str,
+    pub(crate) policy: CoercionPolicy,
+}
+
+#[derive(Default)]
+pub(crate) struct CoercionLog {
+    errors: Vec<CoercionError>,
+    dropped: usize,
+}
+
+impl CoercionLog {
+    pub(crate) fn push(&mut self, error: CoercionError) {
+        if self.errors.len() < MAX_LOGGED {
+            self.errors.push(error);
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    /// Drain into `{ errors: [{ key, column, value, expected, policy }], dropped }`
+    pub(crate) fn take_js(&mut self) -> JsValue {
+        let errors = Array::new();
+        for error in self.errors.drain(..) {
+            let obj = Object::new();
+            Reflect::set(&obj, &JsValue::from_str("key"), &error.key.to_js()).unwrap();
+            Reflect::set(&obj, &JsValue::from_str("column"), &JsValue::from_str(&error.column)).unwrap();
+            Reflect::set(&obj, &JsValue::from_str("value"), &error.value).unwrap();
+            Reflect::set(&obj, &JsValue::from_str("expected"), &JsValue::from_str(error.expected)).unwrap();
+            Reflect::set(&obj, &JsValue::from_str("policy"), &JsValue::from_str(error.policy.as_str())).unwrap();
+            errors.push(&obj);
+        }
+
+        let result = Object::new();
+        Reflect::set(&result, &JsValue::from_str("errors"), &errors).unwrap();
+        Reflect::set(&result, &JsValue::from_str("dropped"), &JsValue::from_f64(self.dropped as f64)).unwrap();
+        self.dropped = 0;
+        result.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_formatted_numbers() {
+        assert_eq!(parse_number("42"), Some(42.0));
+        assert_eq!(parse_number(" -7.5 "), Some(-7.5));
+        assert_eq!(parse_number("1,234.5"), Some(1234.5));
+        assert_eq!(parse_number("-1,234,567"), Some(-1234567.0));
+        assert_eq!(parse_number("1e3"), Some(1000.0));
+    }
+
+    #[test]
+    fn parses_magnitude_suffixes() {
+        assert_eq!(parse_number("1.2k"), Some(1200.0));
+        assert_eq!(parse_number("3.5M"), Some(3_500_000.0));
+        assert_eq!(parse_number("2 b"), Some(2e9));
+        assert_eq!(parse_number("1t"), Some(1e12));
+    }
+
+    #[test]
+    fn empty_text_is_null() {
+        assert!(parse_number("").unwrap().is_nan());
+        assert!(parse_number("   ").unwrap().is_nan());
+    }
+
+    #[test]
+    fn rejects_non_numbers() {
+        for text in ["abc", "inf", "NaN", "k", "1.2x", "12-", "1,23", "$5"] {
+            assert_eq!(parse_number(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn strips_thousands_only_in_groups_of_three() {
+        assert_eq!(strip_thousands("1,234").as_deref(), Some("1234"));
+        assert_eq!(strip_thousands("+12,345.67").as_deref(), Some("+12345.67"));
+        assert_eq!(strip_thousands("1234,567"), None);
+        assert_eq!(strip_thousands("1,2345"), None);
+        assert_eq!(strip_thousands(",123"), None);
+        assert_eq!(strip_thousands("1,234.5,6"), None);
+    }
+
+    #[test]
+    fn integer_columns_by_policy() {
+        assert_eq!(CoercionPolicy::Strict.integer(101.0), Ok(101.0));
+        assert_eq!(CoercionPolicy::Strict.integer(101.5), Err(CellError::Rejected));
+        assert_eq!(CoercionPolicy::Lenient.integer(101.5), Err(CellError::Rounded(102.0)));
+        assert_eq!(CoercionPolicy::Lenient.integer(-2.4), Err(CellError::Rounded(-2.0)));
+        assert_eq!(CoercionPolicy::Legacy.integer(101.5), Ok(101.5));
+        assert!(CoercionPolicy::Strict.integer(f64::NAN).unwrap().is_nan());
+    }
+
+    #[test]
+    fn integer_columns_reject_out_of_range() {
+        let too_big = i32::MAX as f64 + 1.0;
+        assert_eq!(CoercionPolicy::Strict.integer(too_big), Err(CellError::Rejected));
+        assert_eq!(CoercionPolicy::Lenient.integer(too_big), Err(CellError::Rejected));
+        assert_eq!(CoercionPolicy::Lenient.integer(f64::INFINITY), Err(CellError::Rejected));
+        assert_eq!(CoercionPolicy::Strict.integer(i32::MIN as f64), Ok(i32::MIN as f64));
+    }
+}
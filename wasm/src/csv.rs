@@ -0,0 +1,794 @@
+//! CSV import straight into typed columns (`loadCsv`), and export of the
+//! view or selected rows (`exportCsv`)
+//!
+//! RFC 4180 quoting: quoted fields may contain the delimiter, line breaks and
+//! doubled quotes. Lines may end in \n, \r\n or \r; blank lines are skipped
+//! and a UTF-8 BOM is ignored.
+//!
+//! Bad lines don't abort the load - they are skipped (wrong field count,
+//! broken quoting, invalid UTF-8, missing or duplicate key) or loaded with
+//! null cells (text in a number column), and reported by line number.
+//!
+//! Column types come from the store's schema; nothing is inferred from the
+//! data. `lenientNumbers` only widens what number columns accept.
+//!
+//! Export escapes like `escapeCSVValue` in src/utils/csv.ts: values holding
+//! the delimiter, a quote or a line break are quoted, quotes doubled, nulls
+//! left empty, and lines joined by \n.
+
+use crate::coerce::{parse_number, CellError, CoercionPolicy};
+use crate::error::{ErrorCode, GridError};
+use crate::key::RowKey;
+use crate::{Collation, Column, ColumnData, ColumnType, GridStore};
+use js_sys::{Array, Object, Reflect};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::prelude::*;
+
+/// Line errors kept per load - later ones are counted, not stored
+const MAX_LINE_ERRORS: usize = 1000;
+
+struct CsvOptions {
+    delimiter: u8,
+    quote: u8,
+    header: bool,
+    // Column names in file order when there's no header
+    columns: Option<Vec<String>>,
+    // Header name -> schema column name, for headers that don't match
+    mapping: HashMap<String, String>,
+    lenient_numbers: bool,
+    infer_types: bool,
+}
+
+impl CsvOptions {
+    fn parse(options: &JsValue) -> Result<Self, GridError> {
+        let option = |key: &str| {
+            if options.is_object() {
+                Reflect::get(options, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+            } else {
+                JsValue::UNDEFINED
+            }
+        };
+        let byte = |key: &str, default: u8| -> Result<u8, GridError> {
+            let value = option(key);
+            if value.is_undefined() || value.is_null() {
+                return Ok(default);
+            }
+            match value.as_string().as_deref().map(str::as_bytes) {
+                Some(&[b]) if b.is_ascii() && b != b'\n' && b != b'\r' => Ok(b),
+                _ => Err(GridError::new(
+                    ErrorCode::InvalidCsv,
+                    format!("CSV option '{}' must be a single ASCII character, got {:?}", key, value),
+                )),
+            }
+        };
+
+        let delimiter = byte("delimiter", b',')?;
+        let quote = byte("quote", b'"')?;
+        if delimiter == quote {
+            return Err(GridError::new(ErrorCode::InvalidCsv, "CSV delimiter and quote must differ"));
+        }
+
+        let header = option("header");
+        let columns = option("columns");
+        let columns = Array::is_array(&columns)
+            .then(|| Array::from(&columns).iter().map(|name| name.as_string().unwrap_or_default()).collect());
+
+        let mapping = option("mapping");
+        let mapping = if mapping.is_object() {
+            Object::entries(mapping.unchecked_ref())
+                .iter()
+                .filter_map(|entry| {
+                    let entry = Array::from(&entry);
+                    Some((entry.get(0).as_string()?, entry.get(1).as_string()?))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            delimiter,
+            quote,
+            header: header.is_undefined() || header.is_truthy(),
+            columns,
+            mapping,
+            lenient_numbers: option("lenientNumbers").is_truthy(),
+            infer_types: option("inferTypes").is_truthy(),
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl GridStore {
+    /// Load rows from CSV bytes - O(bytes + n * cols)
+    /// Options: `{ delimiter = ",", quote = '"', header = true, columns?,
+    /// mapping?, lenientNumbers = false, inferTypes = false }`
+    /// - header: first line names the columns, matched to the schema by name
+    ///   (exact, then case-insensitive); `mapping: { "Last Px": "price" }`
+    ///   renames headers first. Unmatched headers are ignored unless
+    ///   inferTypes is set.
+    /// - columns: column names in file order when there is no header
+    ///   (default: schema order)
+    /// - lenientNumbers: read human-formatted numbers ("1,234.5", "1.2k") in
+    ///   every number column, as a lenient column would; otherwise only
+    ///   columns with lenient coercion do and the rest take plain decimals
+    /// - integer columns check cells under their coercion policy: rejected
+    ///   cells load as null with a line error, lenient rounding is only logged
+    /// - inferTypes: add each unmatched header as a new column, typed from its
+    ///   cells - integer if every non-empty cell is a 32-bit integer, number
+    ///   if every one parses, else string. Rows already in the store read
+    ///   null there. Needs a header; schema columns keep their types.
+    ///
+    /// Returns `{ rows, errors: [{ line, column?, message }], dropped,
+    /// addedColumns: [{ name, type }] }`
+    #[wasm_bindgen(js_name = loadCsv)]
+    pub fn load_csv(&mut self, bytes: &[u8], options: &JsValue) -> Result<JsValue, GridError> {
+        let options = CsvOptions::parse(options)?;
+        let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+        let mut reader = RecordReader {
+            bytes,
+            pos: 0,
+            line: 1,
+            delimiter: options.delimiter,
+            quote: options.quote,
+        };
+        let mut errors = LineErrors::default();
+        let mut fields = Vec::new();
+
+        // Field position -> column
+        let mapping: Vec<Option<usize>> = if options.header {
+            match reader.next_record(&mut fields) {
+                None => return Ok(errors.into_js(0, &[])),
+                Some(Err((line, message))) => {
+                    return Err(GridError::new(ErrorCode::InvalidCsv, format!("Invalid CSV header on line {}: {}", line, message)));
+                }
+                Some(Ok(_)) => fields.iter().map(|name| self.csv_column(name, &options.mapping)).collect(),
+            }
+        } else if let Some(names) = &options.columns {
+            names.iter().map(|name| self.column_idx(name).map(Some)).collect::<Result<_, _>>()?
+        } else {
+            (0..self.columns.len()).map(Some).collect()
+        };
+
+        let mut mapped = HashSet::new();
+        for col_idx in mapping.iter().flatten() {
+            if !mapped.insert(*col_idx) {
+                let name = &self.columns[*col_idx].name;
+                return Err(GridError::new(ErrorCode::DuplicateColumn, format!("Column '{}' is mapped from more than one CSV field", name))
+                    .on_column(name.clone()));
+            }
+        }
+        if let Some(&col_idx) = self.key_columns.iter().find(|c| !mapped.contains(c)) {
+            let name = &self.columns[col_idx].name;
+            return Err(GridError::new(ErrorCode::MissingKey, format!("CSV has no field for primary key column '{}'", name))
+                .on_column(name.clone()));
+        }
+
+        // Field position -> new column, for unmatched headers under inferTypes
+        let mut added: Vec<String> = Vec::new();
+        let mut added_slots: Vec<Option<usize>> = vec![None; mapping.len()];
+        if options.infer_types && options.header {
+            for (pos, header) in fields.iter().enumerate() {
+                let name = header_name(header, &options.mapping);
+                if mapping[pos].is_some() || name.is_empty() {
+                    continue;
+                }
+                if added.iter().any(|n| n == name) {
+                    return Err(GridError::new(ErrorCode::DuplicateColumn, format!("CSV has more than one field named '{}'", name))
+                        .on_column(name));
+                }
+                added_slots[pos] = Some(added.len());
+                added.push(name.to_string());
+            }
+        }
+        let mut added_cells: Vec<Vec<String>> = vec![Vec::new(); added.len()];
+        let mut row_added: Vec<String> = Vec::new();
+
+        let lenient: Vec<bool> = self.columns
+            .iter()
+            .map(|col| options.lenient_numbers || col.coercion == CoercionPolicy::Lenient)
+            .collect();
+        let mut loaded: Vec<Option<ColumnData>> = (0..self.columns.len())
+            .map(|c| mapped.contains(&c).then(|| self.columns[c].data.nulls_like(0)))
+            .collect();
+        let mut failures = Vec::new();
+        let mut seen = HashSet::new();
+        let mut count = 0;
+        let mut cells: Vec<Option<Cell>> = Vec::new();
+        let mut bad_numbers = Vec::new();
+
+        while let Some(record) = reader.next_record(&mut fields) {
+            let line = match record {
+                Ok(line) => line,
+                Err((line, message)) => {
+                    errors.push(line, None, message);
+                    continue;
+                }
+            };
+            if fields.len() != mapping.len() {
+                errors.push(line, None, format!("expected {} fields, found {}", mapping.len(), fields.len()));
+                continue;
+            }
+
+            cells.clear();
+            cells.resize_with(self.columns.len(), || None);
+            bad_numbers.clear();
+            row_added.clear();
+            row_added.resize(added.len(), String::new());
+            for (pos, (field, &col_idx)) in fields.drain(..).zip(&mapping).enumerate() {
+                let Some(col_idx) = col_idx else {
+                    if let Some(slot) = added_slots[pos] {
+                        row_added[slot] = field;
+                    }
+                    continue;
+                };
+                let col = &self.columns[col_idx];
+                cells[col_idx] = Some(match col.data {
+                    ColumnData::Strings(_) => Cell::Text(field),
+                    ColumnData::Numbers(_) => match parse_cell(&field, lenient[col_idx]) {
+                        // Key cells are left to csv_key, which reports INVALID_KEY
+                        Some(n) if self.key_columns.contains(&col_idx) => Cell::Number(n),
+                        Some(n) => match col.check_number(n) {
+                            Ok(n) => Cell::Number(n),
+                            Err(CellError::Rounded(rounded)) => {
+                                bad_numbers.push((col_idx, field, None));
+                                Cell::Number(rounded)
+                            }
+                            Err(CellError::Rejected) => {
+                                bad_numbers.push((col_idx, field, Some("is not a 32-bit integer")));
+                                Cell::Number(f64::NAN)
+                            }
+                        },
+                        None => {
+                            bad_numbers.push((col_idx, field, Some("is not a number")));
+                            Cell::Number(f64::NAN)
+                        }
+                    },
+                });
+            }
+
+            let key = match self.csv_key(&cells) {
+                Ok(key) if self.id_to_row.contains_key(&key) || seen.contains(&key) => {
+                    errors.push(line, None, format!("Duplicate ID: {}", key));
+                    continue;
+                }
+                Ok(key) => key,
+                Err(e) => {
+                    errors.push(line, e.column, e.message);
+                    continue;
+                }
+            };
+            seen.insert(key);
+
+            // Rejected cells load as null and are reported; rounded ones are only logged
+            for (col_idx, field, reason) in bad_numbers.drain(..) {
+                if let Some(reason) = reason {
+                    let name = self.columns[col_idx].name.clone();
+                    errors.push(line, Some(name), format!("{:?} {}", field, reason));
+                }
+                failures.push((col_idx, count, JsValue::from_str(&field)));
+            }
+            for (data, cell) in loaded.iter_mut().zip(cells.drain(..)) {
+                match (data, cell) {
+                    (Some(ColumnData::Strings(v)), Some(Cell::Text(s))) => v.push(s),
+                    (Some(ColumnData::Numbers(v)), Some(Cell::Number(n))) => v.push(n),
+                    _ => {}
+                }
+            }
+            for (cells, field) in added_cells.iter_mut().zip(row_added.drain(..)) {
+                cells.push(field);
+            }
+            count += 1;
+        }
+
+        let existing = self.row_count;
+        let rows = self.append_columns(loaded, count, failures)?;
+
+        let lenient = options.lenient_numbers || self.default_coercion == CoercionPolicy::Lenient;
+        let mut added_types = Vec::with_capacity(added.len());
+        for (name, cells) in added.into_iter().zip(added_cells) {
+            let col_type = infer_type(&cells, lenient);
+            added_types.push((name.clone(), col_type));
+            self.add_inferred_column(name, col_type, cells, existing, lenient);
+        }
+        Ok(errors.into_js(rows, &added_types))
+    }
+
+    /// Export rows as CSV bytes (UTF-8, no BOM) - O(rows * cols)
+    /// Options: `{ rows?, columns?, delimiter = ",", includeHeaders = true,
+    /// headers?, start = 0, count?, continuation = false }`
+    /// - rows: row indices to export (e.g. a selection) instead of the view;
+    ///   deleted rows are left out
+    /// - columns: projection, in output order (default: every column)
+    /// - headers: header text per column name (default: the column name)
+    /// - start/count: export that slice of the rows for chunking. The header
+    ///   goes with the chunk at 0 and later chunks start with the line break,
+    ///   so chunks concatenate into the same bytes as one export.
+    /// - continuation: these rows follow an earlier chunk (e.g. a slice of a
+    ///   row snapshot passed as `rows`) - no header, start with the line break
+    #[wasm_bindgen(js_name = exportCsv)]
+    pub fn export_csv(&mut self, options: &JsValue) -> Result<Vec<u8>, GridError> {
+        let option = |key: &str| {
+            if options.is_object() {
+                Reflect::get(options, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+            } else {
+                JsValue::UNDEFINED
+            }
+        };
+
+        let columns = self.export_columns(&option("columns"))?;
+        let delimiter = option("delimiter").as_string().unwrap_or_else(|| ",".to_string());
+        if delimiter.is_empty() {
+            return Err(GridError::new(ErrorCode::InvalidCsv, "CSV delimiter must not be empty"));
+        }
+        let include_headers = option("includeHeaders");
+        let include_headers = include_headers.is_undefined() || include_headers.is_truthy();
+        let headers = option("headers");
+
+        let selected = self.export_rows(&option("rows"))?;
+        let rows: &[u32] = match &selected {
+            Some(rows) => rows,
+            None => {
+                self.ensure_view();
+                self.view.cached_view.as_deref().unwrap_or_default()
+            }
+        };
+
+        let start = option("start").as_f64().map_or(0, |n| n as usize).min(rows.len());
+        let end = match option("count").as_f64() {
+            Some(count) => start.saturating_add(count as usize).min(rows.len()),
+            None => rows.len(),
+        };
+
+        let continuation = option("continuation").is_truthy();
+        let mut out = String::new();
+        let with_header = include_headers && start == 0 && !continuation;
+        if with_header {
+            for (i, &col_idx) in columns.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(&delimiter);
+                }
+                push_escaped(&mut out, &self.export_header(&headers, col_idx), &delimiter);
+            }
+        }
+
+        for (pos, &row) in rows[start..end].iter().enumerate() {
+            if with_header || continuation || start + pos > 0 {
+                out.push('\n');
+            }
+            for (i, &col_idx) in columns.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(&delimiter);
+                }
+                match &self.columns[col_idx].data {
+                    ColumnData::Strings(v) => push_escaped(&mut out, &v[row as usize], &delimiter),
+                    ColumnData::Numbers(v) => push_number(&mut out, v[row as usize], &delimiter),
+                }
+            }
+        }
+        Ok(out.into_bytes())
+    }
+}
+
+impl GridStore {
+    /// Schema column for a CSV header - exact name, then case-insensitive
+    fn csv_column(&self, header: &str, mapping: &HashMap<String, String>) -> Option<usize> {
+        let name = header_name(header, mapping);
+        self.column_index.get(name).copied().or_else(|| {
+            self.columns.iter().position(|col| col.name.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Add an unmatched CSV field as a column of the inferred type
+    /// Rows loaded before this CSV (`existing`) read null; not text-indexed.
+    fn add_inferred_column(&mut self, name: String, col_type: ColumnType, cells: Vec<String>, existing: usize, lenient: bool) {
+        let data = match col_type {
+            ColumnType::String => {
+                let mut values = vec![String::new(); existing];
+                values.extend(cells);
+                ColumnData::Strings(values)
+            }
+            ColumnType::Number | ColumnType::Integer => {
+                let mut values = vec![f64::NAN; existing];
+                values.extend(cells.iter().map(|cell| parse_cell(cell, lenient).unwrap_or(f64::NAN)));
+                ColumnData::Numbers(values)
+            }
+        };
+        self.column_index.insert(name.clone(), self.columns.len());
+        self.columns.push(Column {
+            name,
+            col_type,
+            data,
+            indexed: false,
+            index: None,
+            secondary: None,
+            coercion: self.default_coercion,
+            nullable: true,
+            collation: Collation::Binary,
+            format: None,
+            units: None,
+            meta: None,
+        });
+    }
+
+    fn csv_key(&self, cells: &[Option<Cell>]) -> Result<RowKey, GridError> {
+        let parts = self.key_columns
+            .iter()
+            .map(|&col_idx| match &cells[col_idx] {
+                Some(Cell::Text(s)) => Ok(RowKey::Str(s.clone())),
+                Some(Cell::Number(n)) => self.number_key_part(col_idx, *n),
+                None => Err(self.key_error(col_idx, &JsValue::UNDEFINED)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RowKey::from_parts(parts))
+    }
+}
+
+/// Header after `mapping` renames, trimmed
+fn header_name<'a>(header: &'a str, mapping: &'a HashMap<String, String>) -> &'a str {
+    mapping.get(header).map(String::as_str).unwrap_or(header).trim()
+}
+
+/// Type for the cells of an unmatched field - integer if every non-empty
+/// cell is a 32-bit integer, number if every one parses, else string
+/// A field with no values at all is a string column.
+fn infer_type(cells: &[String], lenient: bool) -> ColumnType {
+    let mut integer = true;
+    let mut any = false;
+    for cell in cells {
+        match parse_cell(cell, lenient) {
+            Some(n) if n.is_nan() => {}
+            Some(n) => {
+                any = true;
+                integer &= n.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(&n);
+            }
+            None => return ColumnType::String,
+        }
+    }
+    match (any, integer) {
+        (false, _) => ColumnType::String,
+        (true, true) => ColumnType::Integer,
+        (true, false) => ColumnType::Number,
+    }
+}
+
+/// Quote if the value holds the delimiter, a quote or a line break
+fn push_escaped(out: &mut String, value: &str, delimiter: &str) {
+    if value.contains(delimiter) || value.contains(['"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&value.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(value);
+    }
+}
+
+/// Number as JS's String() would print it - null (NaN) is empty
+fn push_number(out: &mut String, n: f64, delimiter: &str) {
+    if n.is_nan() {
+        return;
+    }
+    // Same shortest round-trip digits; JS switches to exponent form outside
+    // [1e-6, 1e21), prints -0 as "0" and infinities as "Infinity"
+    let text = if n == 0.0 {
+        "0".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n.abs() >= 1e21 || n.abs() < 1e-6 {
+        let text = format!("{:e}", n);
+        if text.contains("e-") { text } else { text.replace('e', "e+") }
+    } else {
+        n.to_string()
+    };
+    push_escaped(out, &text, delimiter);
+}
+
+enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+/// Number cell - empty is null, None if it isn't a number
+fn parse_cell(field: &str, lenient: bool) -> Option<f64> {
+    if lenient {
+        return parse_number(field);
+    }
+    let field = field.trim();
+    if field.is_empty() {
+        return Some(f64::NAN);
+    }
+    // f64::from_str would also take "inf" and "NaN"
+    field.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+#[derive(Default)]
+struct LineErrors {
+    errors: Vec<(usize, Option<String>, String)>,
+    dropped: usize,
+}
+
+impl LineErrors {
+    fn push(&mut self, line: usize, column: Option<String>, message: String) {
+        if self.errors.len() < MAX_LINE_ERRORS {
+            self.errors.push((line, column, message));
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    fn into_js(self, rows: u32, added: &[(String, ColumnType)]) -> JsValue {
+        let errors = Array::new();
+        for (line, column, message) in self.errors {
+            let obj = Object::new();
+            Reflect::set(&obj, &JsValue::from_str("line"), &JsValue::from_f64(line as f64)).unwrap();
+            if let Some(column) = column {
+                Reflect::set(&obj, &JsValue::from_str("column"), &JsValue::from_str(&column)).unwrap();
+            }
+            Reflect::set(&obj, &JsValue::from_str("message"), &JsValue::from_str(&message)).unwrap();
+            errors.push(&obj);
+        }
+
+        let result = Object::new();
+        Reflect::set(&result, &JsValue::from_str("rows"), &JsValue::from_f64(rows as f64)).unwrap();
+        Reflect::set(&result, &JsValue::from_str("errors"), &errors).unwrap();
+        Reflect::set(&result, &JsValue::from_str("dropped"), &JsValue::from_f64(self.dropped as f64)).unwrap();
+
+        let added_columns = Array::new();
+        for (name, col_type) in added {
+            let obj = Object::new();
+            Reflect::set(&obj, &JsValue::from_str("name"), &JsValue::from_str(name)).unwrap();
+            Reflect::set(&obj, &JsValue::from_str("type"), &JsValue::from_str(col_type.as_str())).unwrap();
+            added_columns.push(&obj);
+        }
+        Reflect::set(&result, &JsValue::from_str("addedColumns"), &added_columns).unwrap();
+        result.into()
+    }
+}
+
+// ============================================================================
+// Record reader
+// ============================================================================
+
+/// Splits bytes into records - the delimiter and quote are ASCII, so
+/// splitting bytes never cuts a UTF-8 sequence
+struct RecordReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,  // 1-based, of the next unread byte
+    delimiter: u8,
+    quote: u8,
+}
+
+impl RecordReader<'_> {
+    /// Next non-blank record into `fields` - Ok(line it starts on), or
+    /// Err((line, message)) for a malformed record, which is skipped
+    fn next_record(&mut self, fields: &mut Vec<String>) -> Option<Result<usize, (usize, String)>> {
+        while self.pos < self.bytes.len() && self.at_line_end() {
+            self.end_line();
+        }
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+
+        let line = self.line;
+        fields.clear();
+        loop {
+            let field = if self.bytes.get(self.pos) == Some(&self.quote) {
+                self.quoted_field()
+            } else {
+                Ok(self.unquoted_field())
+            };
+            let field = match field.and_then(|raw| {
+                String::from_utf8(raw).map_err(|_| format!("field {} is not valid UTF-8", fields.len() + 1))
+            }) {
+                Ok(field) => field,
+                Err(message) => {
+                    self.skip_line();
+                    return Some(Err((line, message)));
+                }
+            };
+            fields.push(field);
+
+            match self.bytes.get(self.pos) {
+                None => break,
+                Some(&b) if b == self.delimiter => self.pos += 1,
+                Some(_) if self.at_line_end() => {
+                    self.end_line();
+                    break;
+                }
+                Some(&b) => {
+                    self.skip_line();
+                    return Some(Err((line, format!("unexpected {:?} after closing quote", b as char))));
+                }
+            }
+        }
+        Some(Ok(line))
+    }
+
+    fn unquoted_field(&mut self) -> Vec<u8> {
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != self.delimiter && !self.at_line_end() {
+            self.pos += 1;
+        }
+        self.bytes[start..self.pos].to_vec()
+    }
+
+    fn quoted_field(&mut self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        self.pos += 1;
+        loop {
+            let Some(offset) = self.bytes[self.pos..].iter().position(|&b| b == self.quote) else {
+                self.pos = self.bytes.len();
+                return Err("unterminated quoted field".to_string());
+            };
+            let chunk = &self.bytes[self.pos..self.pos + offset];
+            self.line += chunk.iter().filter(|&&b| b == b'\n').count()
+                + chunk.windows(2).filter(|w| w[0] == b'\r' && w[1] != b'\n').count()
+                + (chunk.last() == Some(&b'\r')) as usize;
+            out.extend_from_slice(chunk);
+            self.pos += offset + 1;
+
+            // Doubled quote is a literal quote
+            if self.bytes.get(self.pos) == Some(&self.quote) {
+                out.push(self.quote);
+                self.pos += 1;
+            } else {
+                return Ok(out);
+            }
+        }
+    }
+
+    fn at_line_end(&self) -> bool {
+        matches!(self.bytes.get(self.pos), Some(b'\n' | b'\r'))
+    }
+
+    /// Step over \n, \r\n or \r
+    fn end_line(&mut self) {
+        if self.bytes[self.pos] == b'\r' && self.bytes.get(self.pos + 1) == Some(&b'\n') {
+            self.pos += 1;
+        }
+        self.pos += 1;
+        self.line += 1;
+    }
+
+    fn skip_line(&mut self) {
+        while self.pos < self.bytes.len() && !self.at_line_end() {
+            self.pos += 1;
+        }
+        if self.pos < self.bytes.len() {
+            self.end_line();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader(bytes: &[u8]) -> RecordReader<'_> {
+        RecordReader {
+            bytes,
+            pos: 0,
+            line: 1,
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+
+    /// A record as (line, fields) or (line, error)
+    type Record = Result<(usize, Vec<String>), (usize, String)>;
+
+    fn records(bytes: &[u8]) -> Vec<Record> {
+        let mut reader = reader(bytes);
+        let mut fields = Vec::new();
+        let mut out = Vec::new();
+        while let Some(record) = reader.next_record(&mut fields) {
+            out.push(record.map(|line| (line, fields.clone())));
+        }
+        out
+    }
+
+    fn ok(line: usize, fields: &[&str]) -> Record {
+        Ok((line, fields.iter().map(|f| f.to_string()).collect()))
+    }
+
+    #[test]
+    fn splits_plain_records() {
+        assert_eq!(records(b"a,b,c\n1,,3"), vec![ok(1, &["a", "b", "c"]), ok(2, &["1", "", "3"])]);
+    }
+
+    #[test]
+    fn handles_crlf_cr_and_blank_lines() {
+        assert_eq!(
+            records(b"a,b\r\n\r\n1,2\r3,4\r\n"),
+            vec![ok(1, &["a", "b"]), ok(3, &["1", "2"]), ok(4, &["3", "4"])],
+        );
+    }
+
+    #[test]
+    fn unquotes_fields() {
+        assert_eq!(
+            records(b"\"a,b\",\"say \"\"hi\"\"\",\"\"\n"),
+            vec![ok(1, &["a,b", "say \"hi\"", ""])],
+        );
+    }
+
+    #[test]
+    fn keeps_line_breaks_in_quoted_fields() {
+        assert_eq!(
+            records(b"\"one\ntwo\",x\n\"three\r\nfour\",y\nz,w"),
+            vec![ok(1, &["one\ntwo", "x"]), ok(3, &["three\r\nfour", "y"]), ok(5, &["z", "w"])],
+        );
+    }
+
+    #[test]
+    fn reports_and_skips_bad_lines() {
+        let out = records(b"\"a\"b,c\nok,1\n\"open");
+        assert_eq!(out.len(), 3);
+        assert!(matches!(&out[0], Err((1, message)) if message.contains("after closing quote")));
+        assert_eq!(out[1], ok(2, &["ok", "1"]));
+        assert!(matches!(&out[2], Err((3, message)) if message.contains("unterminated")));
+    }
+
+    #[test]
+    fn reports_invalid_utf8_by_line() {
+        let out = records(b"a,b\nx,\xff\xfe\n\"\xc3\",y\nc,d");
+        assert_eq!(out[0], ok(1, &["a", "b"]));
+        assert_eq!(out[1], Err((2, "field 2 is not valid UTF-8".to_string())));
+        assert_eq!(out[2], Err((3, "field 1 is not valid UTF-8".to_string())));
+        assert_eq!(out[3], ok(4, &["c", "d"]));
+    }
+
+    #[test]
+    fn honors_custom_delimiter_and_quote() {
+        let mut reader = reader(b"'a\tb'\tc\n");
+        reader.delimiter = b'\t';
+        reader.quote = b'\'';
+        let mut fields = Vec::new();
+        assert_eq!(reader.next_record(&mut fields), Some(Ok(1)));
+        assert_eq!(fields, vec!["a\tb", "c"]);
+        assert_eq!(reader.next_record(&mut fields), None);
+    }
+
+    #[test]
+    fn parses_number_cells() {
+        assert_eq!(parse_cell(" 1.5 ", false), Some(1.5));
+        assert!(parse_cell("", false).unwrap().is_nan());
+        assert_eq!(parse_cell("inf", false), None);
+        assert_eq!(parse_cell("1,234", false), None);
+        assert_eq!(parse_cell("1,234", true), Some(1234.0));
+        assert_eq!(parse_cell("1.2k", true), Some(1200.0));
+    }
+
+    #[test]
+    fn prints_numbers_like_js() {
+        let print = |n: f64| {
+            let mut out = String::new();
+            push_number(&mut out, n, ",");
+            out
+        };
+        assert_eq!(print(f64::NAN), "");
+        assert_eq!(print(-0.0), "0");
+        assert_eq!(print(1.5), "1.5");
+        assert_eq!(print(1e21), "1e+21");
+        assert_eq!(print(1.5e-7), "1.5e-7");
+        assert_eq!(print(f64::INFINITY), "Infinity");
+        assert_eq!(print(f64::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn infers_unmatched_field_types() {
+        let infer = |values: &[&str], lenient: bool| {
+            let cells: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            infer_type(&cells, lenient).as_str()
+        };
+        assert_eq!(infer(&["1", "", "-42"], false), "integer");
+        assert_eq!(infer(&["1", "2.5"], false), "number");
+        assert_eq!(infer(&["1", "3000000000"], false), "number");
+        assert_eq!(infer(&["1", "n/a"], false), "string");
+        assert_eq!(infer(&["1,234", "5"], false), "string");
+        assert_eq!(infer(&["1,234", "5"], true), "integer");
+        assert_eq!(infer(&["", " "], false), "string");
+        assert_eq!(infer(&[], false), "string");
+    }
+}
@@ -0,0 +1,132 @@
+//! Structured errors for the JS API
+//!
+//! Crossed into JS as a regular `Error` (so `instanceof Error` and stack
+//! traces keep working) with extra machine-readable properties:
+//! `{ name: "GridError", code: "INVALID_REGEX", message, position?, column? }`
+
+use crate::binary::DecodeError;
+use js_sys::Reflect;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ErrorCode {
+    // Schema
+    InvalidSchema,
+    UnknownColumnType,
+    UnknownIndexType,
+    DuplicateColumn,
+    MissingPrimaryKey,
+    MultiplePrimaryKeys,
+    PrimaryKeyColumn,
+    // Columns
+    UnknownColumn,
+    ColumnTypeMismatch,
+    ColumnLengthMismatch,
+    // Keys
+    MissingKey,
+    InvalidKey,
+    DuplicateKey,
+    RowNotFound,
+    // Input
+    InvalidBinary,
+    InvalidSnapshot,
+    SnapshotVersion,
+    InvalidArrow,
+    UnsupportedArrowType,
+    InvalidCsv,
+    TooManyRows,
+    InvalidRegex,
+    RegexTooLarge,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidSchema => "INVALID_SCHEMA",
+            ErrorCode::UnknownColumnType => "UNKNOWN_COLUMN_TYPE",
+            ErrorCode::UnknownIndexType => "UNKNOWN_INDEX_TYPE",
+            ErrorCode::DuplicateColumn => "DUPLICATE_COLUMN",
+            ErrorCode::MissingPrimaryKey => "MISSING_PRIMARY_KEY",
+            ErrorCode::MultiplePrimaryKeys => "MULTIPLE_PRIMARY_KEYS",
+            ErrorCode::PrimaryKeyColumn => "PRIMARY_KEY_COLUMN",
+            ErrorCode::UnknownColumn => "UNKNOWN_COLUMN",
+            ErrorCode::ColumnTypeMismatch => "COLUMN_TYPE_MISMATCH",
+            ErrorCode::ColumnLengthMismatch => "COLUMN_LENGTH_MISMATCH",
+            ErrorCode::MissingKey => "MISSING_KEY",
+            ErrorCode::InvalidKey => "INVALID_KEY",
+            ErrorCode::DuplicateKey => "DUPLICATE_KEY",
+            ErrorCode::RowNotFound => "ROW_NOT_FOUND",
+            ErrorCode::InvalidBinary => "INVALID_BINARY",
+            ErrorCode::InvalidSnapshot => "INVALID_SNAPSHOT",
+            ErrorCode::SnapshotVersion => "UNSUPPORTED_SNAPSHOT_VERSION",
+            ErrorCode::InvalidArrow => "INVALID_ARROW",
+            ErrorCode::UnsupportedArrowType => "UNSUPPORTED_ARROW_TYPE",
+            ErrorCode::InvalidCsv => "INVALID_CSV",
+            ErrorCode::TooManyRows => "TOO_MANY_ROWS",
+            ErrorCode::InvalidRegex => "INVALID_REGEX",
+            ErrorCode::RegexTooLarge => "REGEX_TOO_LARGE",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GridError {
+    pub code: ErrorCode,
+    pub message: String,
+    // Offset into the offending input: char in a regex pattern, byte in a
+    // binary batch, entry in a schema array
+    pub position: Option<usize>,
+    // Column the error is about, if any
+    pub column: Option<String>,
+}
+
+impl GridError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            position: None,
+            column: None,
+        }
+    }
+
+    pub fn at(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn on_column(mut self, column: impl Into<String>) -> Self {
+        self.column = Some(column.into());
+        self
+    }
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code.as_str(), self.message)
+    }
+}
+
+impl std::error::Error for GridError {}
+
+impl From<GridError> for JsValue {
+    fn from(err: GridError) -> JsValue {
+        let js_err = js_sys::Error::new(&err.message);
+        js_err.set_name("GridError");
+        Reflect::set(&js_err, &JsValue::from_str("code"), &JsValue::from_str(err.code.as_str())).unwrap();
+        if let Some(position) = err.position {
+            Reflect::set(&js_err, &JsValue::from_str("position"), &JsValue::from_f64(position as f64)).unwrap();
+        }
+        if let Some(column) = &err.column {
+            Reflect::set(&js_err, &JsValue::from_str("column"), &JsValue::from_str(column)).unwrap();
+        }
+        js_err.into()
+    }
+}
+
+impl From<DecodeError> for GridError {
+    fn from(err: DecodeError) -> GridError {
+        GridError::new(ErrorCode::InvalidBinary, err.to_string()).at(err.offset)
+    }
+}
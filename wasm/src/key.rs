@@ -0,0 +1,62 @@
+//! Primary keys
+//!
+//! A key is one string column, one integer column, or a composite of several
+//! key columns (declared via the constructor's `options.primaryKey`).
+
+use js_sys::Array;
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+/// Largest integer an f64 (and so a JS number) represents exactly
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum RowKey {
+    Str(String),
+    Int(i64),
+    Composite(Vec<RowKey>),
+}
+
+impl RowKey {
+    /// Integer key from a JS number - None for NaN, fractions and unsafe integers
+    pub(crate) fn int(n: f64) -> Option<RowKey> {
+        (n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER).then_some(RowKey::Int(n as i64))
+    }
+
+    /// One part stays a plain key, several make a composite
+    pub(crate) fn from_parts(mut parts: Vec<RowKey>) -> RowKey {
+        if parts.len() == 1 {
+            parts.pop().unwrap()
+        } else {
+            RowKey::Composite(parts)
+        }
+    }
+
+    /// Back to the form update/delete accept: string, number or array of parts
+    pub(crate) fn to_js(&self) -> JsValue {
+        match self {
+            RowKey::Str(s) => JsValue::from_str(s),
+            RowKey::Int(n) => JsValue::from_f64(*n as f64),
+            RowKey::Composite(parts) => parts.iter().map(RowKey::to_js).collect::<Array>().into(),
+        }
+    }
+}
+
+impl fmt::Display for RowKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowKey::Str(s) => f.write_str(s),
+            RowKey::Int(n) => write!(f, "{}", n),
+            RowKey::Composite(parts) => {
+                f.write_str("(")?;
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", part)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
@@ -1,6 +1,29 @@
 use wasm_bindgen::prelude::*;
-use js_sys::{Array, Object, Reflect, Uint32Array};
+use js_sys::{Array, Float64Array, Int32Array, Object, Reflect, Uint32Array, Uint8Array};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
+
+pub mod binary;
+mod coerce;
+mod csv;
+pub mod error;
+mod key;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod regex_filter;
+mod secondary;
+mod snapshot;
+mod text;
+mod xlsx;
+
+use binary::{BatchDecoder, Value, ValueKind};
+use coerce::{CellError, CoercionError, CoercionLog, CoercionPolicy};
+use error::{ErrorCode, GridError};
+use key::RowKey;
+use regex_filter::RegexFilter;
+use secondary::{IndexKey, IndexKind, SecondaryIndex};
+use text::{merge_ranges, similarity, tokenize_query, Normalizer};
 
 // ============================================================================
 // Initialization
@@ -12,6 +35,53 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+/// WASM linear memory - pair with the *Ptr/*Len accessors for zero-copy reads:
+/// `new Uint32Array(wasmMemory().buffer, store.viewPtr(), store.viewLen())`
+#[wasm_bindgen(js_name = wasmMemory)]
+pub fn wasm_memory() -> JsValue {
+    wasm_bindgen::memory()
+}
+
+// ============================================================================
+// Stateless helpers (used by the src/wasm/index.ts bridge)
+// ============================================================================
+
+/// Row indices produced by a stateless sort/filter call
+#[wasm_bindgen]
+pub struct IndexResult {
+    indices: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl IndexResult {
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Uint32Array {
+        Uint32Array::from(&self.indices[..])
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[wasm_bindgen(getter, js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+/// Indices of values with `min <= value <= max` - O(n), NaN never matches
+#[wasm_bindgen]
+pub fn filter_range(values: &[f64], min: f64, max: f64) -> IndexResult {
+    let indices = values
+        .iter()
+        .enumerate()
+        .filter(|&(_, &v)| v >= min && v <= max)
+        .map(|(i, _)| i as u32)
+        .collect();
+    IndexResult { indices }
+}
+
 // ============================================================================
 // Column Data Types
 // ============================================================================
@@ -37,17 +107,18 @@ impl ColumnData {
         }
     }
 
-    fn get_string(&self, idx: usize) -> Option<&str> {
+    /// Column of the same kind holding `count` nulls
+    fn nulls_like(&self, count: usize) -> ColumnData {
         match self {
-            ColumnData::Strings(v) => v.get(idx).map(|s| s.as_str()),
-            ColumnData::Numbers(v) => None,
+            ColumnData::Strings(_) => ColumnData::Strings(vec![String::new(); count]),
+            ColumnData::Numbers(_) => ColumnData::Numbers(vec![f64::NAN; count]),
         }
     }
 
-    fn get_number(&self, idx: usize) -> Option<f64> {
+    fn get_string(&self, idx: usize) -> Option<&str> {
         match self {
-            ColumnData::Numbers(v) => v.get(idx).copied().filter(|n| !n.is_nan()),
-            ColumnData::Strings(_) => None,
+            ColumnData::Strings(v) => v.get(idx).map(|s| s.as_str()),
+            ColumnData::Numbers(_) => None,
         }
     }
 
@@ -67,6 +138,112 @@ impl ColumnData {
         }
     }
 
+    /// Convert a JS column (typed array or plain array) into column data
+    /// Cells failing coercion become null (rounded, for lenient integers) and
+    /// are appended to `failures` as (offset, value).
+    fn from_js_column(
+        col_type: ColumnType,
+        policy: CoercionPolicy,
+        value: &JsValue,
+        failures: &mut Vec<(usize, JsValue)>,
+    ) -> ColumnData {
+        let mut number = |i: usize, result: Result<f64, CellError>, v: JsValue| {
+            result.unwrap_or_else(|e| {
+                failures.push((i, v));
+                match e {
+                    CellError::Rounded(n) => n,
+                    CellError::Rejected => f64::NAN,
+                }
+            })
+        };
+        match col_type {
+            ColumnType::Number | ColumnType::Integer if js_sys::ArrayBuffer::is_view(value) => {
+                // Typed arrays convert in one bulk copy
+                let mut data = ColumnData::Numbers(Float64Array::new(value).to_vec());
+                if col_type == ColumnType::Integer {
+                    data.check_integers(policy, failures);
+                }
+                data
+            }
+            ColumnType::Number | ColumnType::Integer => {
+                let arr = Array::from(value);
+                ColumnData::Numbers(
+                    arr.iter()
+                        .enumerate()
+                        .map(|(i, v)| number(i, policy.number(&v, col_type), v))
+                        .collect(),
+                )
+            }
+            ColumnType::String => {
+                let arr = Array::from(value);
+                ColumnData::Strings(
+                    arr.iter()
+                        .enumerate()
+                        .map(|(i, v)| policy.string(&v).unwrap_or_else(|_| {
+                            failures.push((i, v));
+                            String::new()
+                        }))
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Run number cells through the policy's integer check - failed cells
+    /// become null (rounded, for lenient) and are appended to `failures`
+    /// as (offset, value)
+    fn check_integers(&mut self, policy: CoercionPolicy, failures: &mut Vec<(usize, JsValue)>) {
+        let ColumnData::Numbers(values) = self else { return };
+        for (i, n) in values.iter_mut().enumerate() {
+            if let Err(e) = policy.integer(*n) {
+                failures.push((i, JsValue::from_f64(*n)));
+                *n = match e {
+                    CellError::Rounded(rounded) => rounded,
+                    CellError::Rejected => f64::NAN,
+                };
+            }
+        }
+    }
+
+    fn set_value(&mut self, idx: usize, val: Value) {
+        match val {
+            Value::String(s) => self.set_string(idx, s),
+            Value::Number(n) => self.set_number(idx, n),
+        }
+    }
+
+    fn value_kind(&self) -> ValueKind {
+        match self {
+            ColumnData::Strings(_) => ValueKind::String,
+            ColumnData::Numbers(_) => ValueKind::Number,
+        }
+    }
+
+    /// Secondary index key of one cell
+    fn index_key(&self, idx: usize) -> IndexKey {
+        match self {
+            ColumnData::Strings(v) => IndexKey::Text(v[idx].clone()),
+            ColumnData::Numbers(v) => IndexKey::number(v[idx]),
+        }
+    }
+
+    /// Compare one cell against an index key without allocating
+    fn cmp_key(&self, idx: usize, key: &IndexKey) -> Ordering {
+        match (self, key) {
+            (ColumnData::Strings(v), IndexKey::Text(k)) => v[idx].as_str().cmp(k),
+            (ColumnData::Numbers(v), _) => IndexKey::number(v[idx]).cmp(key),
+            _ => self.index_key(idx).cmp(key),
+        }
+    }
+
+    /// Missing cell - "" in string columns, NaN in number columns
+    fn is_null(&self, idx: usize) -> bool {
+        match self {
+            ColumnData::Strings(v) => v[idx].is_empty(),
+            ColumnData::Numbers(v) => v[idx].is_nan(),
+        }
+    }
+
     fn to_js_value(&self, idx: usize) -> JsValue {
         match self {
             ColumnData::Strings(v) => {
@@ -80,101 +257,679 @@ impl ColumnData {
             }
         }
     }
+
+    /// Gather the given rows into one JS value for columnar transfer
+    /// Numbers -> Float64Array (NaN for null)
+    /// Integers -> { values: Int32Array, nulls: Uint8Array } (nulls[i] = 1, values[i] = 0)
+    /// Strings -> { codes: Int32Array, values: string[] } (dictionary encoded)
+    fn gather_js(&self, rows: &[u32], integer: bool) -> JsValue {
+        match self {
+            ColumnData::Numbers(v) if integer => {
+                // `as` saturates out-of-range cells, which only legacy coercion lets in
+                let values: Vec<i32> = rows.iter().map(|&r| v[r as usize] as i32).collect();
+                let nulls: Vec<u8> = rows.iter().map(|&r| v[r as usize].is_nan() as u8).collect();
+
+                let obj = Object::new();
+                Reflect::set(&obj, &JsValue::from_str("values"), &Int32Array::from(&values[..])).unwrap();
+                Reflect::set(&obj, &JsValue::from_str("nulls"), &Uint8Array::from(&nulls[..])).unwrap();
+                obj.into()
+            }
+            ColumnData::Numbers(v) => {
+                let values: Vec<f64> = rows.iter().map(|&r| v[r as usize]).collect();
+                Float64Array::from(&values[..]).into()
+            }
+            ColumnData::Strings(v) => {
+                let mut dict: HashMap<&str, i32> = HashMap::new();
+                let values = Array::new();
+                let codes: Vec<i32> = rows
+                    .iter()
+                    .map(|&r| {
+                        let s = v[r as usize].as_str();
+                        *dict.entry(s).or_insert_with(|| {
+                            values.push(&JsValue::from_str(s));
+                            (values.length() - 1) as i32
+                        })
+                    })
+                    .collect();
+
+                let obj = Object::new();
+                Reflect::set(&obj, &JsValue::from_str("codes"), &Int32Array::from(&codes[..])).unwrap();
+                Reflect::set(&obj, &JsValue::from_str("values"), &values).unwrap();
+                obj.into()
+            }
+        }
+    }
+}
+
+/// Declared column type - "integer" is stored like "number" but reported as declared
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+    String,
+    Number,
+    Integer,
+}
+
+impl ColumnType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(ColumnType::String),
+            "number" => Some(ColumnType::Number),
+            "integer" => Some(ColumnType::Integer),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ColumnType::String => "string",
+            ColumnType::Number => "number",
+            ColumnType::Integer => "integer",
+        }
+    }
+}
+
+/// How string columns compare when sorting
+#[derive(Clone, Copy, PartialEq)]
+enum Collation {
+    Binary,  // Code point order (default)
+    NoCase,  // Case-insensitive
+}
+
+impl Collation {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "binary" => Some(Collation::Binary),
+            "nocase" => Some(Collation::NoCase),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Collation::Binary => "binary",
+            Collation::NoCase => "nocase",
+        }
+    }
 }
 
 #[derive(Clone)]
 struct Column {
     name: String,
+    col_type: ColumnType,
     data: ColumnData,
     indexed: bool,  // Include in trigram search
+    index: Option<IndexKind>,  // Declared via schema `index`
+    secondary: Option<SecondaryIndex>,  // Declared index, or built lazily for numeric ranges/sorts
+    coercion: CoercionPolicy,  // Schema `coercion`, else the store default
+    nullable: bool,  // Declared only - not enforced
+    collation: Collation,
+
+    // Display metadata, passed through untouched
+    format: Option<String>,  // e.g. "price", "timestamp"
+    units: Option<String>,
+    meta: Option<JsValue>,  // Free-form object, None if not given
+}
+
+impl Column {
+    /// Check a number bound for this column - integer columns apply the
+    /// coercion policy's 32-bit check, other number columns take it as-is
+    fn check_number(&self, n: f64) -> Result<f64, CellError> {
+        match self.col_type {
+            ColumnType::Integer => self.coercion.integer(n),
+            _ => Ok(n),
+        }
+    }
+
+    /// Parse one schema entry - returns the column and whether it's flagged primaryKey
+    fn from_def(def: &JsValue, default_coercion: CoercionPolicy) -> Result<(Column, bool), GridError> {
+        let field = |key: &str| Reflect::get(def, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED);
+
+        let name = field("name").as_string()
+            .ok_or_else(|| GridError::new(ErrorCode::InvalidSchema, "Column must have a string 'name'"))?;
+        let col_type = field("type").as_string().ok_or_else(|| {
+            GridError::new(ErrorCode::InvalidSchema, format!("Column '{}' must have a string 'type'", name))
+                .on_column(name.clone())
+        })?;
+
+        let col_type = ColumnType::parse(&col_type).ok_or_else(|| {
+            GridError::new(ErrorCode::UnknownColumnType, format!("Unknown column type: {}", col_type))
+                .on_column(name.clone())
+        })?;
+        let data = match col_type {
+            ColumnType::String => ColumnData::Strings(Vec::new()),
+            ColumnType::Number | ColumnType::Integer => ColumnData::Numbers(Vec::new()),
+        };
+
+        let index = field("index");
+        let index = if index.is_undefined() || index.is_null() {
+            None
+        } else {
+            let kind = index.as_string().as_deref().and_then(IndexKind::parse).ok_or_else(|| {
+                GridError::new(ErrorCode::UnknownIndexType, format!("Unknown index type on column '{}': {:?}", name, index))
+                    .on_column(name.clone())
+            })?;
+            Some(kind)
+        };
+
+        let coercion = parse_coercion(field("coercion"), default_coercion)
+            .map_err(|e| e.on_column(name.clone()))?;
+
+        let collation = field("collation");
+        let collation = if collation.is_undefined() || collation.is_null() {
+            Collation::Binary
+        } else {
+            collation.as_string().as_deref().and_then(Collation::parse).ok_or_else(|| {
+                GridError::new(
+                    ErrorCode::InvalidSchema,
+                    format!("Unknown collation on column '{}': {:?} - expected binary or nocase", name, collation),
+                )
+                .on_column(name.clone())
+            })?
+        };
+
+        let nullable = field("nullable");
+        let column = Column {
+            name,
+            col_type,
+            data,
+            indexed: field("indexed").is_truthy(),
+            index,
+            secondary: index.map(SecondaryIndex::new),
+            coercion,
+            nullable: nullable.is_undefined() || nullable.is_truthy(),
+            collation,
+            format: field("format").as_string(),
+            units: field("units").as_string(),
+            meta: Some(field("meta")).filter(|meta| !meta.is_undefined()),
+        };
+        Ok((column, field("primaryKey").is_truthy()))
+    }
+
+    /// Index key for a JS lookup value - null/undefined look up nulls the way
+    /// they're stored ("" in string columns), any other wrong type is an error
+    fn key_from_js(&self, value: &JsValue) -> Result<IndexKey, GridError> {
+        let missing = value.is_undefined() || value.is_null();
+        let key = match &self.data {
+            ColumnData::Strings(_) if missing => Some(IndexKey::Text(String::new())),
+            ColumnData::Strings(_) => value.as_string().map(IndexKey::Text),
+            ColumnData::Numbers(_) if missing => Some(IndexKey::Null),
+            ColumnData::Numbers(_) => value.as_f64().map(IndexKey::number),
+        };
+        key.ok_or_else(|| {
+            GridError::new(
+                ErrorCode::ColumnTypeMismatch,
+                format!("Column '{}' is {}, got {:?}", self.name, self.col_type.as_str(), value),
+            )
+            .on_column(self.name.clone())
+        })
+    }
+
+    /// Secondary index usable for ordering - its key order is binary, so
+    /// case-insensitive string columns can't use it
+    fn ordered_index(&self) -> Option<&SecondaryIndex> {
+        match (&self.data, self.collation) {
+            (ColumnData::Strings(_), Collation::NoCase) => None,
+            _ => self.secondary.as_ref(),
+        }
+    }
+
+    /// Full schema entry - `key_position` is the column's place in the
+    /// primary key, reported as keyPosition for composite keys
+    fn to_def(&self, key_position: Option<usize>, composite_key: bool) -> JsValue {
+        let obj = Object::new();
+        let set = |key: &str, value: &JsValue| {
+            Reflect::set(&obj, &JsValue::from_str(key), value).unwrap();
+        };
+        set("name", &JsValue::from_str(&self.name));
+        set("type", &JsValue::from_str(self.col_type.as_str()));
+        set("primaryKey", &JsValue::from_bool(key_position.is_some()));
+        if let (Some(pos), true) = (key_position, composite_key) {
+            set("keyPosition", &JsValue::from_f64(pos as f64));
+        }
+        set("indexed", &JsValue::from_bool(self.indexed));
+        if let Some(index) = self.index {
+            set("index", &JsValue::from_str(index.as_str()));
+        }
+        set("nullable", &JsValue::from_bool(self.nullable));
+        set("collation", &JsValue::from_str(self.collation.as_str()));
+        set("coercion", &JsValue::from_str(self.coercion.as_str()));
+        if let Some(format) = &self.format {
+            set("format", &JsValue::from_str(format));
+        }
+        if let Some(units) = &self.units {
+            set("units", &JsValue::from_str(units));
+        }
+        if let Some(meta) = &self.meta {
+            set("meta", meta);
+        }
+        obj.into()
+    }
+}
+
+/// Coercion policy option - null/undefined means `default`
+fn parse_coercion(value: JsValue, default: CoercionPolicy) -> Result<CoercionPolicy, GridError> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(default);
+    }
+    value.as_string().as_deref().and_then(CoercionPolicy::parse).ok_or_else(|| {
+        GridError::new(
+            ErrorCode::InvalidSchema,
+            format!("Unknown coercion policy {:?} - expected legacy, strict or lenient", value),
+        )
+    })
 }
 
 // ============================================================================
 // Incremental Trigram Index
 // ============================================================================
 
-struct TrigramIndex {
-    // trigram (3 bytes) -> set of row indices
-    index: HashMap<[u8; 3], HashSet<u32>>,
+type Trigram = [char; 3];
+// Unigrams are stored as [c, '\0'] so both short forms share one map
+type ShortGram = [char; 2];
+
+/// Rows per bulk index build - the (gram, row) pairs of a build take 3-4x
+/// the memory of the postings they become, so big loads go in chunks
+const BULK_INDEX_ROWS: usize = 64 * 1024;
+
+/// Separates indexed columns in the combined row text
+/// n-grams never span it, so a match always lies within one column.
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// n-gram -> posting list (sorted, unique row indices)
+struct PostingMap<K> {
+    lists: HashMap<K, Vec<u32>>,
+    // Lists emptied by remove() and kept for reuse until the next prune
+    empty_lists: usize,
 }
 
-impl TrigramIndex {
+impl<K: std::hash::Hash + Eq + Ord + Copy> PostingMap<K> {
+    /// Prune once empty lists exceed this many and a quarter of all lists
+    const PRUNE_MIN_EMPTY: usize = 1024;
+
     fn new() -> Self {
         Self {
-            index: HashMap::new(),
+            lists: HashMap::new(),
+            empty_lists: 0,
+        }
+    }
+
+    /// Add a row under each gram - O(grams * log(list_len))
+    /// Rows are usually appended in increasing order, which is O(1) per list.
+    fn add(&mut self, row: u32, grams: &[K]) {
+        for gram in grams {
+            let list = self.lists.entry(*gram).or_default();
+            if list.capacity() > 0 && list.is_empty() {
+                self.empty_lists -= 1;
+            }
+            match list.last() {
+                Some(&last) if last >= row => {
+                    if let Err(pos) = list.binary_search(&row) {
+                        list.insert(pos, row);
+                    }
+                }
+                _ => list.push(row),
+            }
+        }
+    }
+
+    /// Add many (gram, row) pairs at once - O(pairs * log(pairs))
+    /// Sorts the pairs and emits each posting list in one go instead of
+    /// inserting every pair into its list individually.
+    fn add_bulk(&mut self, mut pairs: Vec<(K, u32)>) {
+        pairs.sort_unstable();
+
+        for group in pairs.chunk_by(|a, b| a.0 == b.0) {
+            let list = self.lists.entry(group[0].0).or_default();
+            if list.capacity() > 0 && list.is_empty() {
+                self.empty_lists -= 1;
+            }
+            let appends_in_order = list.last().is_none_or(|&last| last < group[0].1);
+            list.extend(group.iter().map(|&(_, row)| row));
+            if !appends_in_order {
+                list.sort_unstable();
+                list.dedup();
+            }
+        }
+    }
+
+    /// Remove a row from each gram's list - O(grams * log(list_len))
+    fn remove(&mut self, row: u32, grams: &[K]) {
+        for gram in grams {
+            if let Some(list) = self.lists.get_mut(gram) {
+                if let Ok(pos) = list.binary_search(&row) {
+                    list.remove(pos);
+                    // Keep the empty list around - it is likely reused soon
+                    if list.is_empty() {
+                        self.empty_lists += 1;
+                    }
+                }
+            }
         }
+
+        // ...but don't let churn leak memory
+        if self.empty_lists > Self::PRUNE_MIN_EMPTY && self.empty_lists * 4 > self.lists.len() {
+            self.prune();
+        }
+    }
+
+    /// Drop empty posting lists and release slack capacity - O(num_lists)
+    /// Returns number of lists removed
+    fn prune(&mut self) -> usize {
+        let before = self.lists.len();
+        self.lists.retain(|_, list| {
+            if list.capacity() > list.len() * 2 {
+                list.shrink_to_fit();
+            }
+            !list.is_empty()
+        });
+        self.lists.shrink_to_fit();
+        self.empty_lists = 0;
+        before - self.lists.len()
     }
 
-    fn generate_trigrams(text: &str) -> Vec<[u8; 3]> {
-        let lower = text.to_lowercase();
-        let bytes = lower.as_bytes();
-        if bytes.len() < 3 {
+    /// Intersect the posting lists of all grams - results in ascending row order
+    fn intersect(&self, grams: &[K]) -> Vec<u32> {
+        let mut lists = Vec::with_capacity(grams.len());
+        for gram in grams {
+            match self.lists.get(gram) {
+                Some(list) if !list.is_empty() => lists.push(list.as_slice()),
+                // Gram not in index - no matches
+                _ => return vec![],
+            }
+        }
+        if lists.is_empty() {
             return vec![];
         }
-        (0..bytes.len() - 2)
-            .map(|i| [bytes[i], bytes[i + 1], bytes[i + 2]])
-            .collect()
+
+        // Start from the rarest gram so every intersection step is cheap
+        lists.sort_unstable_by_key(|list| list.len());
+
+        let mut result = lists[0].to_vec();
+        for list in &lists[1..] {
+            result = intersect_sorted(&result, list);
+            if result.is_empty() {
+                break;
+            }
+        }
+        result
+    }
+
+    fn postings(&self) -> usize {
+        self.lists.values().map(|list| list.len()).sum()
+    }
+
+    /// Approximate heap usage of the map and its lists
+    fn estimated_bytes(&self) -> usize {
+        // hashbrown: one (key, value) slot plus one control byte per bucket
+        let slot_bytes = std::mem::size_of::<(K, Vec<u32>)>() + 1;
+        let list_bytes: usize = self.lists.values()
+            .map(|list| list.capacity() * std::mem::size_of::<u32>())
+            .sum();
+        self.lists.capacity() * slot_bytes + list_bytes
+    }
+
+    fn clear(&mut self) {
+        self.lists.clear();
+        self.empty_lists = 0;
+    }
+}
+
+/// Trigram index for substring search, plus a unigram/bigram index so
+/// 1-2 char queries are answered without a full scan
+struct TrigramIndex {
+    trigrams: PostingMap<Trigram>,
+    short_grams: PostingMap<ShortGram>,
+    // Shared with the filter check so both see the same text
+    normalizer: Normalizer,
+}
+
+/// Snapshot of index size for memory budgeting
+struct TrigramStats {
+    trigrams: usize,
+    short_grams: usize,
+    empty_lists: usize,
+    postings: usize,
+    estimated_bytes: usize,
+    largest: Vec<(Trigram, usize)>,
+}
+
+impl TrigramIndex {
+    fn new() -> Self {
+        Self {
+            trigrams: PostingMap::new(),
+            short_grams: PostingMap::new(),
+            normalizer: Normalizer::default(),
+        }
+    }
+
+    /// Unique trigrams and short grams of the normalized text, sorted
+    /// Char-based, so multi-byte characters never get split.
+    fn generate_grams(&self, text: &str) -> (Vec<Trigram>, Vec<ShortGram>) {
+        let chars = self.normalizer.normalize_chars(text);
+
+        let mut trigrams: Vec<Trigram> = chars
+            .windows(3)
+            .filter(|w| !w.contains(&FIELD_SEPARATOR))
+            .map(|w| [w[0], w[1], w[2]])
+            .collect();
+        trigrams.sort_unstable();
+        trigrams.dedup();
+
+        let mut short: Vec<ShortGram> = chars
+            .iter()
+            .filter(|&&c| c != FIELD_SEPARATOR)
+            .map(|&c| [c, '\0'])
+            .chain(
+                chars.windows(2)
+                    .filter(|w| !w.contains(&FIELD_SEPARATOR))
+                    .map(|w| [w[0], w[1]]),
+            )
+            .collect();
+        short.sort_unstable();
+        short.dedup();
+
+        (trigrams, short)
     }
 
     /// Add a row to the index - O(text_length)
     fn add(&mut self, row: u32, text: &str) {
-        for trigram in Self::generate_trigrams(text) {
-            self.index.entry(trigram).or_default().insert(row);
+        let (trigrams, short) = self.generate_grams(text);
+        self.trigrams.add(row, &trigrams);
+        self.short_grams.add(row, &short);
+    }
+
+    /// Add many rows at once - O(total_grams * log(total_grams))
+    fn add_bulk(&mut self, rows: &[(u32, String)]) {
+        let mut trigram_pairs = Vec::new();
+        let mut short_pairs = Vec::new();
+        for (row, text) in rows {
+            let (trigrams, short) = self.generate_grams(text);
+            trigram_pairs.extend(trigrams.into_iter().map(|t| (t, *row)));
+            short_pairs.extend(short.into_iter().map(|g| (g, *row)));
         }
+        self.trigrams.add_bulk(trigram_pairs);
+        self.short_grams.add_bulk(short_pairs);
     }
 
     /// Remove a row from the index - O(text_length)
     fn remove(&mut self, row: u32, text: &str) {
-        for trigram in Self::generate_trigrams(text) {
-            if let Some(set) = self.index.get_mut(&trigram) {
-                set.remove(&row);
-                // Don't remove empty sets - they might be reused
-            }
-        }
+        let (trigrams, short) = self.generate_grams(text);
+        self.trigrams.remove(row, &trigrams);
+        self.short_grams.remove(row, &short);
     }
 
     /// Update a row in the index - O(old_len + new_len)
+    /// Only grams the row gains or loses touch their posting lists.
     fn update(&mut self, row: u32, old_text: &str, new_text: &str) {
         // Only update if text actually changed
-        if old_text != new_text {
-            self.remove(row, old_text);
-            self.add(row, new_text);
+        if old_text == new_text {
+            return;
         }
+        let (old_trigrams, old_short) = self.generate_grams(old_text);
+        let (new_trigrams, new_short) = self.generate_grams(new_text);
+
+        let (lost, gained) = sorted_diff(&old_trigrams, &new_trigrams);
+        self.trigrams.remove(row, &lost);
+        self.trigrams.add(row, &gained);
+
+        let (lost, gained) = sorted_diff(&old_short, &new_short);
+        self.short_grams.remove(row, &lost);
+        self.short_grams.add(row, &gained);
     }
 
-    /// Search for rows matching query - O(num_matches)
+    /// Search for candidate rows matching query - O(rarest_list * num_trigrams)
+    /// Returns candidates in ascending row order; they still need verifying.
+    /// Queries shorter than 3 chars go through search_short() instead.
     fn search(&self, query: &str) -> Vec<u32> {
-        let trigrams = Self::generate_trigrams(query);
+        let (trigrams, _) = self.generate_grams(query);
+        self.trigrams.intersect(&trigrams)
+    }
 
-        if trigrams.is_empty() {
-            // Query too short for trigrams - caller should do full scan
-            return vec![];
-        }
+    /// Exact rows containing a 1-2 char query - no verification needed
+    fn search_short(&self, query: &str) -> Vec<u32> {
+        let chars = self.normalizer.normalize_chars(query);
+        let gram = match chars[..] {
+            [c] => [c, '\0'],
+            [a, b] => [a, b],
+            _ => return vec![],
+        };
+        self.short_grams.intersect(&[gram])
+    }
 
-        // Intersect posting lists
-        let mut result: Option<HashSet<u32>> = None;
+    /// Rows that could fuzzily match `term` (already normalized chars)
+    /// Uses the q-gram lemma: a token within k edits of the term still shares
+    /// at least (bigrams - 2k) of its bigrams. Returns ascending row order.
+    fn fuzzy_candidates(&self, term: &[char], threshold: f64) -> Vec<u32> {
+        let mut grams: Vec<ShortGram> = match term {
+            [] => return vec![],
+            [c] => vec![[*c, '\0']],
+            _ => term.windows(2).map(|w| [w[0], w[1]]).collect(),
+        };
+        grams.sort_unstable();
+        grams.dedup();
 
-        for trigram in &trigrams {
-            match self.index.get(trigram) {
-                Some(posting_list) => {
-                    result = Some(match result {
-                        None => posting_list.clone(),
-                        Some(existing) => existing.intersection(posting_list).copied().collect(),
-                    });
-                }
-                None => {
-                    // Trigram not in index - no matches
-                    return vec![];
+        let max_edits = (term.len() as f64 * (1.0 - threshold)).floor() as usize;
+        let min_hits = grams.len().saturating_sub(2 * max_edits).max(1);
+
+        let mut hits: HashMap<u32, usize> = HashMap::new();
+        for gram in &grams {
+            if let Some(list) = self.short_grams.lists.get(gram) {
+                for &row in list {
+                    *hits.entry(row).or_default() += 1;
                 }
             }
         }
 
-        result.map(|s| s.into_iter().collect()).unwrap_or_default()
+        let mut rows: Vec<u32> = hits
+            .into_iter()
+            .filter(|&(_, count)| count >= min_hits)
+            .map(|(row, _)| row)
+            .collect();
+        rows.sort_unstable();
+        rows
+    }
+
+    /// Drop empty posting lists - returns number of lists removed
+    fn prune(&mut self) -> usize {
+        self.trigrams.prune() + self.short_grams.prune()
+    }
+
+    fn stats(&self, top: usize) -> TrigramStats {
+        let mut largest: Vec<(Trigram, usize)> =
+            self.trigrams.lists.iter().map(|(t, list)| (*t, list.len())).collect();
+        let top = top.min(largest.len());
+        if top > 0 && top < largest.len() {
+            largest.select_nth_unstable_by(top - 1, |a, b| b.1.cmp(&a.1));
+        }
+        largest.truncate(top);
+        largest.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let empty_lists = self.trigrams.empty_lists + self.short_grams.empty_lists;
+        TrigramStats {
+            trigrams: self.trigrams.lists.len() - self.trigrams.empty_lists,
+            short_grams: self.short_grams.lists.len() - self.short_grams.empty_lists,
+            empty_lists,
+            postings: self.trigrams.postings() + self.short_grams.postings(),
+            estimated_bytes: self.trigrams.estimated_bytes() + self.short_grams.estimated_bytes(),
+            largest,
+        }
     }
 
     fn clear(&mut self) {
-        self.index.clear();
+        self.trigrams.clear();
+        self.short_grams.clear();
+    }
+}
+
+/// Intersect two sorted posting lists
+/// Gallops through the longer list when sizes are lopsided, merges otherwise.
+fn intersect_sorted(small: &[u32], large: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(small.len().min(large.len()));
+
+    if small.len() * 16 < large.len() {
+        let mut base = 0;
+        for &row in small {
+            base += gallop(&large[base..], row);
+            if base >= large.len() {
+                break;
+            }
+            if large[base] == row {
+                result.push(row);
+                base += 1;
+            }
+        }
+    } else {
+        let (mut i, mut j) = (0, 0);
+        while i < small.len() && j < large.len() {
+            match small[i].cmp(&large[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    result.push(small[i]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Elements only in `old` and only in `new` - both sorted and unique
+fn sorted_diff<K: Ord + Copy>(old: &[K], new: &[K]) -> (Vec<K>, Vec<K>) {
+    let (mut lost, mut gained) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        match old[i].cmp(&new[j]) {
+            Ordering::Less => {
+                lost.push(old[i]);
+                i += 1;
+            }
+            Ordering::Greater => {
+                gained.push(new[j]);
+                j += 1;
+            }
+            Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
     }
+    lost.extend_from_slice(&old[i..]);
+    gained.extend_from_slice(&new[j..]);
+    (lost, gained)
+}
+
+/// Index of the first element >= target (exponential then binary search)
+fn gallop(list: &[u32], target: u32) -> usize {
+    let mut bound = 1;
+    while bound < list.len() && list[bound - 1] < target {
+        bound *= 2;
+    }
+    let lo = bound / 2;
+    let hi = bound.min(list.len());
+    lo + list[lo..hi].partition_point(|&row| row < target)
 }
 
 // ============================================================================
@@ -189,12 +944,38 @@ pub enum SortDir {
     None = 2,
 }
 
+// ============================================================================
+// Search Mode
+// ============================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    Substring = 0,  // Every term is an exact (normalized) substring
+    Fuzzy = 1,      // Typo-tolerant: terms scored against tokens, see fuzzyThreshold
+    Regex = 2,      // Filter text is a regex, matched per indexed column
+}
+
 // ============================================================================
 // View State
 // ============================================================================
 
+/// `min <= column <= max` on a numeric column, bounds inclusive
+struct RangeFilter {
+    column: usize,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
 struct ViewState {
-    filter_text: String,
+    filter_text: String,  // As typed
+    // Normalized (same as the trigram index) terms, all must match
+    filter_terms: Vec<String>,
+    search_mode: SearchMode,
+    fuzzy_threshold: f64,
+    rank_by_relevance: bool,  // Fuzzy only: order by score instead of sort column
+    filter_regex: Option<RegexFilter>,  // Compiled filter text in regex mode
+    range_filters: Vec<RangeFilter>,  // ANDed with the text filter
     sort_column: Option<usize>,
     sort_dir: SortDir,
 
@@ -206,6 +987,12 @@ impl ViewState {
     fn new() -> Self {
         Self {
             filter_text: String::new(),
+            filter_terms: Vec::new(),
+            search_mode: SearchMode::Substring,
+            fuzzy_threshold: 0.5,
+            rank_by_relevance: false,
+            filter_regex: None,
+            range_filters: Vec::new(),
             sort_column: None,
             sort_dir: SortDir::None,
             cached_view: None,
@@ -226,11 +1013,13 @@ pub struct GridStore {
     columns: Vec<Column>,
     column_index: HashMap<String, usize>,
     row_count: usize,
-    id_column: usize,
-    id_to_row: HashMap<String, u32>,
+    key_columns: Vec<usize>,  // Primary key, one column or composite
+    id_to_row: HashMap<RowKey, u32>,
     deleted: Vec<bool>,  // Soft-delete flags
     trigram_index: TrigramIndex,
     indexed_columns: Vec<usize>,
+    default_coercion: CoercionPolicy,  // For columns added later
+    coercion_errors: CoercionLog,
     view: ViewState,
 }
 
@@ -238,66 +1027,113 @@ pub struct GridStore {
 impl GridStore {
     /// Create a new GridStore with the given schema
     /// Schema format: [{ name: "id", type: "string", primaryKey: true, indexed: true }, ...]
+    /// Key columns may be strings or integers. For a composite key pass
+    /// `options.primaryKey: ["account", "symbol"]` instead of flagging columns.
+    /// `index: "hash" | "sorted"` adds a secondary index for findRows / findRowsInRange
+    /// `coercion: "legacy" | "strict" | "lenient"` sets how wrongly typed values
+    /// are handled, per column or store-wide via `options.coercion` (default legacy)
     #[wasm_bindgen(constructor)]
-    pub fn new(schema: &JsValue) -> Result<GridStore, JsError> {
+    pub fn new(schema: &JsValue, options: &JsValue) -> Result<GridStore, GridError> {
+        if !Array::is_array(schema) {
+            return Err(GridError::new(ErrorCode::InvalidSchema, "Schema must be an array of column definitions"));
+        }
+        let option = |key: &str| {
+            if options.is_object() {
+                Reflect::get(options, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+            } else {
+                JsValue::UNDEFINED
+            }
+        };
+        let default_coercion = parse_coercion(option("coercion"), CoercionPolicy::Legacy)?;
+
         let schema_arr = Array::from(schema);
-        let mut columns = Vec::new();
+        let mut columns: Vec<Column> = Vec::new();
         let mut column_index = HashMap::new();
-        let mut id_column = 0;
+        let mut flagged_keys = Vec::new();
         let mut indexed_columns = Vec::new();
 
         for i in 0..schema_arr.length() {
-            let col_def = schema_arr.get(i);
-
-            let name = Reflect::get(&col_def, &JsValue::from_str("name"))
-                .map_err(|_| JsError::new("Column must have 'name'"))?
-                .as_string()
-                .ok_or_else(|| JsError::new("Column name must be string"))?;
-
-            let col_type = Reflect::get(&col_def, &JsValue::from_str("type"))
-                .map_err(|_| JsError::new("Column must have 'type'"))?
-                .as_string()
-                .ok_or_else(|| JsError::new("Column type must be string"))?;
+            let pos = i as usize;
+            let (column, is_primary) = Column::from_def(&schema_arr.get(i), default_coercion)
+                .map_err(|e| e.at(pos))?;
+            if column_index.contains_key(&column.name) {
+                return Err(GridError::new(ErrorCode::DuplicateColumn, format!("Duplicate column name: {}", column.name))
+                    .at(pos)
+                    .on_column(column.name));
+            }
 
-            let is_primary = Reflect::get(&col_def, &JsValue::from_str("primaryKey"))
-                .map(|v| v.is_truthy())
-                .unwrap_or(false);
+            if is_primary {
+                flagged_keys.push(pos);
+            }
 
-            let is_indexed = Reflect::get(&col_def, &JsValue::from_str("indexed"))
-                .map(|v| v.is_truthy())
-                .unwrap_or(false);
+            if column.indexed {
+                indexed_columns.push(pos);
+            }
 
-            let data = match col_type.as_str() {
-                "string" => ColumnData::Strings(Vec::new()),
-                "number" | "integer" => ColumnData::Numbers(Vec::new()),
-                _ => return Err(JsError::new(&format!("Unknown column type: {}", col_type))),
-            };
+            column_index.insert(column.name.clone(), pos);
+            columns.push(column);
+        }
 
-            if is_primary {
-                id_column = i as usize;
+        let composite = option("primaryKey");
+        let key_columns = if Array::is_array(&composite) {
+            if !flagged_keys.is_empty() {
+                return Err(GridError::new(
+                    ErrorCode::MultiplePrimaryKeys,
+                    "Declare the primary key either with primaryKey flags or options.primaryKey, not both",
+                ));
             }
-
-            if is_indexed {
-                indexed_columns.push(i as usize);
+            let mut key_columns = Vec::new();
+            for name in Array::from(&composite).iter() {
+                let name = name.as_string().unwrap_or_default();
+                let col_idx = *column_index.get(&name).ok_or_else(|| {
+                    GridError::new(ErrorCode::UnknownColumn, format!("Unknown primary key column: {}", name))
+                        .on_column(name.clone())
+                })?;
+                if key_columns.contains(&col_idx) {
+                    return Err(GridError::new(ErrorCode::DuplicateColumn, format!("Primary key lists '{}' twice", name))
+                        .on_column(name));
+                }
+                key_columns.push(col_idx);
             }
+            key_columns
+        } else {
+            flagged_keys
+        };
 
-            column_index.insert(name.clone(), i as usize);
-            columns.push(Column {
-                name,
-                data,
-                indexed: is_indexed,
-            });
+        match key_columns.len() {
+            0 => {
+                return Err(GridError::new(
+                    ErrorCode::MissingPrimaryKey,
+                    "Schema has no primary key - flag one column with primaryKey: true or pass options.primaryKey",
+                ));
+            }
+            1 => {}
+            _ if !Array::is_array(&composite) => {
+                let names: Vec<&str> = key_columns.iter().map(|&c| columns[c].name.as_str()).collect();
+                return Err(GridError::new(
+                    ErrorCode::MultiplePrimaryKeys,
+                    format!(
+                        "Columns {} are all flagged primaryKey - for a composite key pass options.primaryKey: [\"{}\"]",
+                        names.join(", "),
+                        names.join("\", \""),
+                    ),
+                )
+                .at(key_columns[1]));
+            }
+            _ => {}
         }
 
         Ok(GridStore {
             columns,
             column_index,
             row_count: 0,
-            id_column,
+            key_columns,
             id_to_row: HashMap::new(),
             deleted: Vec::new(),
             trigram_index: TrigramIndex::new(),
             indexed_columns,
+            default_coercion,
+            coercion_errors: CoercionLog::default(),
             view: ViewState::new(),
         })
     }
@@ -305,39 +1141,118 @@ impl GridStore {
     /// Load rows from JSON array - O(n * cols)
     /// Returns number of rows loaded
     #[wasm_bindgen(js_name = loadRows)]
-    pub fn load_rows(&mut self, rows: &JsValue) -> Result<u32, JsError> {
+    pub fn load_rows(&mut self, rows: &JsValue) -> Result<u32, GridError> {
         let rows_arr = Array::from(rows);
         let count = rows_arr.length();
 
-        // Pre-allocate
-        for col in &mut self.columns {
-            match &mut col.data {
-                ColumnData::Strings(v) => v.reserve(count as usize),
-                ColumnData::Numbers(v) => v.reserve(count as usize),
-            }
-        }
-        self.deleted.reserve(count as usize);
+        self.reserve_rows(count as usize);
 
-        for i in 0..count {
-            let row = rows_arr.get(i);
-            self.insert_row_internal(&row)?;
-        }
+        // Insert without indexing, then build the trigram index in bulk
+        let first_row = self.row_count as u32;
+        let result = (0..count).try_for_each(|i| {
+            self.insert_row_internal(&rows_arr.get(i)).map(|_| ())
+        });
+        self.index_rows_from(first_row);
+
+        self.view.invalidate();
+        result?;
+        Ok(count)
+    }
+
+    /// Load rows from a binary batch (see `binary` module) - O(n * cols)
+    /// Returns number of rows loaded
+    #[wasm_bindgen(js_name = loadRowsBinary)]
+    pub fn load_rows_binary(&mut self, bytes: &[u8]) -> Result<u32, GridError> {
+        let kinds = self.value_kinds();
+        let mut decoder = BatchDecoder::new(bytes, &kinds)?;
+        self.reserve_rows(decoder.capacity_hint());
+
+        let first_row = self.row_count as u32;
+        let mut cells = Vec::with_capacity(kinds.len());
+        let mut count = 0u32;
+        let result = loop {
+            match decoder.next_row(&mut cells) {
+                Ok(true) => {}
+                Ok(false) => break Ok(()),
+                Err(e) => break Err(GridError::from(e)),
+            }
+            if let Err(e) = self.insert_cells(&cells) {
+                break Err(e);
+            }
+            count += 1;
+        };
+        self.index_rows_from(first_row);
 
         self.view.invalidate();
+        result?;
         Ok(count)
     }
 
+    /// Load column arrays in bulk - O(n * cols), no per-row JS objects
+    /// Accepts the `set_data(columns)` shape (array in schema order) or an
+    /// object keyed by column name: { id: [...], price: Float64Array, ... }
+    /// Numeric columns may be any typed array or a plain array; missing
+    /// columns are filled with nulls. Returns number of rows loaded.
+    #[wasm_bindgen(js_name = loadColumns)]
+    pub fn load_columns(&mut self, columns: &JsValue) -> Result<u32, GridError> {
+        let by_position = Array::is_array(columns);
+        let positional = Array::from(columns);
+
+        // Pull every column across the boundary first
+        let mut loaded: Vec<Option<ColumnData>> = Vec::with_capacity(self.columns.len());
+        let mut count: Option<usize> = None;
+        let mut failures: Vec<(usize, usize, JsValue)> = Vec::new();
+        let mut column_failures = Vec::new();
+        for (col_idx, col) in self.columns.iter().enumerate() {
+            let value = if by_position {
+                positional.get(col_idx as u32)
+            } else {
+                Reflect::get(columns, &JsValue::from_str(&col.name)).unwrap_or(JsValue::UNDEFINED)
+            };
+            if value.is_undefined() || value.is_null() {
+                loaded.push(None);
+                continue;
+            }
+
+            // Key cells skip the integer check so a bad key fails key
+            // validation (INVALID_KEY) instead of loading as null
+            let col_type = match col.col_type {
+                ColumnType::Integer if self.key_columns.contains(&col_idx) => ColumnType::Number,
+                col_type => col_type,
+            };
+            let data = ColumnData::from_js_column(col_type, col.coercion, &value, &mut column_failures);
+            failures.extend(column_failures.drain(..).map(|(row, value)| (col_idx, row, value)));
+            match count {
+                None => count = Some(data.len()),
+                Some(n) if n != data.len() => {
+                    return Err(GridError::new(
+                        ErrorCode::ColumnLengthMismatch,
+                        format!("Column '{}' has {} values, expected {}", col.name, data.len(), n),
+                    )
+                    .on_column(col.name.clone()));
+                }
+                _ => {}
+            }
+            loaded.push(Some(data));
+        }
+        self.append_columns(loaded, count.unwrap_or(0), failures)
+    }
+
     /// Insert a single row - O(cols + indexed_text_len)
-    pub fn insert(&mut self, row: &JsValue) -> Result<u32, JsError> {
+    pub fn insert(&mut self, row: &JsValue) -> Result<u32, GridError> {
         let row_idx = self.insert_row_internal(row)?;
+        self.index_rows_from(row_idx);
         self.view.invalidate();
         Ok(row_idx)
     }
 
-    /// Update a row by ID - O(cols + indexed_text_len)
-    pub fn update(&mut self, id: &str, changes: &JsValue) -> Result<(), JsError> {
-        let row_idx = *self.id_to_row.get(id)
-            .ok_or_else(|| JsError::new(&format!("Row not found: {}", id)))?;
+    /// Update a row by key - O(cols + indexed_text_len)
+    /// `key` is the key value, an array of parts for composite keys, or an
+    /// object carrying the key columns. Key columns themselves aren't changed.
+    pub fn update(&mut self, key: &JsValue, changes: &JsValue) -> Result<(), GridError> {
+        let key = self.key_from_js(key)?;
+        let row_idx = *self.id_to_row.get(&key)
+            .ok_or_else(|| GridError::new(ErrorCode::RowNotFound, format!("Row not found: {}", key)))?;
 
         // Get old indexed text for trigram update
         let old_indexed_text = self.get_indexed_text(row_idx as usize);
@@ -349,8 +1264,10 @@ impl GridStore {
         for i in 0..keys.length() {
             let key = keys.get(i).as_string().unwrap();
             if let Some(&col_idx) = self.column_index.get(&key) {
-                let value = Reflect::get(changes, &JsValue::from_str(&key)).unwrap();
-                self.set_cell_value(row_idx as usize, col_idx, &value);
+                if !self.key_columns.contains(&col_idx) {
+                    let value = Reflect::get(changes, &JsValue::from_str(&key)).unwrap();
+                    self.set_cell_value(row_idx as usize, col_idx, &value)?;
+                }
             }
         }
 
@@ -364,34 +1281,33 @@ impl GridStore {
 
     /// Batch update multiple rows - O(updates * (cols + indexed_text_len))
     /// Updates format: [{ id: "row1", field1: value1, ... }, ...]
+    /// Each update carries its key columns; rows with unknown keys are skipped.
     #[wasm_bindgen(js_name = batchUpdate)]
-    pub fn batch_update(&mut self, updates: &JsValue) -> Result<u32, JsError> {
+    pub fn batch_update(&mut self, updates: &JsValue) -> Result<u32, GridError> {
         let updates_arr = Array::from(updates);
         let mut count = 0u32;
 
         for i in 0..updates_arr.length() {
             let update = updates_arr.get(i);
 
-            // Get ID
-            let id = Reflect::get(&update, &JsValue::from_str("id"))
-                .ok()
-                .and_then(|v| v.as_string());
+            // Get key
+            let key = self.key_from_object(&update).ok();
 
-            if let Some(id) = id {
-                if let Some(&row_idx) = self.id_to_row.get(&id) {
+            if let Some(key) = key {
+                if let Some(&row_idx) = self.id_to_row.get(&key) {
                     // Get old indexed text
                     let old_indexed_text = self.get_indexed_text(row_idx as usize);
 
-                    // Apply all fields except 'id'
+                    // Apply all fields except the key
                     let obj = Object::from(update.clone());
-                    let keys = Object::keys(&obj);
-
-                    for j in 0..keys.length() {
-                        let key = keys.get(j).as_string().unwrap();
-                        if key != "id" {
-                            if let Some(&col_idx) = self.column_index.get(&key) {
-                                let value = Reflect::get(&update, &JsValue::from_str(&key)).unwrap();
-                                self.set_cell_value(row_idx as usize, col_idx, &value);
+                    let fields = Object::keys(&obj);
+
+                    for j in 0..fields.length() {
+                        let field = fields.get(j).as_string().unwrap();
+                        if let Some(&col_idx) = self.column_index.get(&field) {
+                            if !self.key_columns.contains(&col_idx) {
+                                let value = Reflect::get(&update, &JsValue::from_str(&field)).unwrap();
+                                self.set_cell_value(row_idx as usize, col_idx, &value)?;
                             }
                         }
                     }
@@ -412,15 +1328,86 @@ impl GridStore {
         Ok(count)
     }
 
-    /// Delete a row by ID (soft delete) - O(1)
-    pub fn delete(&mut self, id: &str) -> Result<(), JsError> {
-        let row_idx = *self.id_to_row.get(id)
-            .ok_or_else(|| JsError::new(&format!("Row not found: {}", id)))?;
+    /// Batch update from a binary batch (see `binary` module)
+    /// Each row must carry the key columns; other present columns are applied.
+    /// Rows with unknown keys are skipped. Returns number of rows updated.
+    #[wasm_bindgen(js_name = batchUpdateBinary)]
+    pub fn batch_update_binary(&mut self, bytes: &[u8]) -> Result<u32, GridError> {
+        let kinds = self.value_kinds();
+        let mut decoder = BatchDecoder::new(bytes, &kinds)?;
+
+        let mut cells = Vec::with_capacity(kinds.len());
+        let mut count = 0u32;
+        let result = loop {
+            match decoder.next_row(&mut cells) {
+                Ok(true) => {}
+                Ok(false) => break Ok(()),
+                Err(e) => break Err(GridError::from(e)),
+            }
+            let key = match self.key_from_cells(&cells) {
+                Ok(key) => key,
+                Err(e) => break Err(e),
+            };
+            let Some(&row_idx) = self.id_to_row.get(&key) else { continue };
+
+            let old_indexed_text = self.get_indexed_text(row_idx as usize);
+            let row = row_idx as usize;
+            let mut written = Ok(());
+            for (col_idx, cell) in cells.iter().enumerate() {
+                if self.key_columns.contains(&col_idx) {
+                    continue;
+                }
+                written = match *cell {
+                    Some(Value::Number(n)) => {
+                        let result = self.columns[col_idx].check_number(n);
+                        self.set_number_cell(row, col_idx, result, || JsValue::from_f64(n))
+                    }
+                    Some(value) => {
+                        self.write_cell(row, col_idx, |data| data.set_value(row, value));
+                        Ok(())
+                    }
+                    None => Ok(()),
+                };
+                if written.is_err() {
+                    break;
+                }
+            }
+            let new_indexed_text = self.get_indexed_text(row_idx as usize);
+            self.trigram_index.update(row_idx, &old_indexed_text, &new_indexed_text);
+            if let Err(e) = written {
+                break Err(e);
+            }
+
+            count += 1;
+        };
+
+        // Rows applied before an error stay applied - the view must see them
+        if count > 0 {
+            self.view.invalidate();
+        }
+
+        result?;
+        Ok(count)
+    }
+
+    /// Delete a row by key (soft delete) - O(1)
+    /// Accepts the same key forms as update()
+    pub fn delete(&mut self, key: &JsValue) -> Result<(), GridError> {
+        let key = self.key_from_js(key)?;
+        let row_idx = *self.id_to_row.get(&key)
+            .ok_or_else(|| GridError::new(ErrorCode::RowNotFound, format!("Row not found: {}", key)))?;
 
         // Remove from trigram index
         let indexed_text = self.get_indexed_text(row_idx as usize);
         self.trigram_index.remove(row_idx, &indexed_text);
 
+        // Secondary indexes only hold live rows
+        for col in &mut self.columns {
+            if let Some(index) = &mut col.secondary {
+                index.remove(&col.data.index_key(row_idx as usize), row_idx);
+            }
+        }
+
         // Soft delete
         self.deleted[row_idx as usize] = true;
 
@@ -429,12 +1416,75 @@ impl GridStore {
     }
 
     /// Set filter text - triggers view recomputation
+    /// Space-separated terms must all match (any order, any indexed column);
+    /// "quoted phrases" match as exact substrings.
+    /// In regex mode an invalid pattern throws (code INVALID_REGEX) and
+    /// leaves the current filter in place.
     #[wasm_bindgen(js_name = setFilter)]
-    pub fn set_filter(&mut self, search: &str) {
+    pub fn set_filter(&mut self, search: &str) -> Result<(), GridError> {
         if self.view.filter_text != search {
-            self.view.filter_text = search.to_lowercase();
+            if self.view.search_mode == SearchMode::Regex {
+                self.view.filter_regex = Self::compile_filter_regex(search)?;
+            }
+            self.view.filter_text = search.to_string();
+            self.view.filter_terms = self.parse_filter_terms(search);
+            self.view.invalidate();
+        }
+        Ok(())
+    }
+
+    /// Set how the filter text matches rows (substring, fuzzy or regex)
+    /// Switching to regex compiles the current filter text and may throw.
+    #[wasm_bindgen(js_name = setSearchMode)]
+    pub fn set_search_mode(&mut self, mode: SearchMode) -> Result<(), GridError> {
+        if self.view.search_mode != mode {
+            self.view.filter_regex = match mode {
+                SearchMode::Regex => Self::compile_filter_regex(&self.view.filter_text)?,
+                _ => None,
+            };
+            self.view.search_mode = mode;
             self.view.invalidate();
         }
+        Ok(())
+    }
+
+    /// Minimum fuzzy score (0..1) every term must reach - default 0.5
+    #[wasm_bindgen(js_name = setFuzzyThreshold)]
+    pub fn set_fuzzy_threshold(&mut self, threshold: f64) {
+        let threshold = threshold.clamp(0.0, 1.0);
+        if self.view.fuzzy_threshold != threshold {
+            self.view.fuzzy_threshold = threshold;
+            if self.view.search_mode == SearchMode::Fuzzy {
+                self.view.invalidate();
+            }
+        }
+    }
+
+    /// In fuzzy mode, order the view by relevance instead of the sort column
+    #[wasm_bindgen(js_name = setRankByRelevance)]
+    pub fn set_rank_by_relevance(&mut self, enabled: bool) {
+        if self.view.rank_by_relevance != enabled {
+            self.view.rank_by_relevance = enabled;
+            if self.view.search_mode == SearchMode::Fuzzy {
+                self.view.invalidate();
+            }
+        }
+    }
+
+    /// Enable/disable diacritic folding for search ("societe" matches "Société")
+    /// Rebuilds the trigram index - O(n * indexed_text_len)
+    #[wasm_bindgen(js_name = setDiacriticFolding)]
+    pub fn set_diacritic_folding(&mut self, enabled: bool) {
+        let normalizer = Normalizer { fold_diacritics: enabled };
+        if self.trigram_index.normalizer == normalizer {
+            return;
+        }
+
+        self.trigram_index.normalizer = normalizer;
+        self.rebuild_text_index();
+
+        self.view.filter_terms = self.parse_filter_terms(&self.view.filter_text);
+        self.view.invalidate();
     }
 
     /// Set sort column and direction
@@ -456,143 +1506,1018 @@ impl GridStore {
     pub fn clear_filter(&mut self) {
         if !self.view.filter_text.is_empty() {
             self.view.filter_text.clear();
+            self.view.filter_terms.clear();
+            self.view.filter_regex = None;
             self.view.invalidate();
         }
     }
 
-    /// Clear sort
-    #[wasm_bindgen(js_name = clearSort)]
-    pub fn clear_sort(&mut self) {
-        if self.view.sort_column.is_some() {
-            self.view.sort_column = None;
-            self.view.sort_dir = SortDir::None;
-            self.view.invalidate();
+    /// Restrict the view to `min <= column <= max` on a numeric column
+    /// Either bound may be omitted; nulls never match. Replaces any existing
+    /// range on the column and is ANDed with the text filter.
+    /// Served by the column's sorted index - O(log n + matches).
+    #[wasm_bindgen(js_name = setRangeFilter)]
+    pub fn set_range_filter(&mut self, column: &str, min: Option<f64>, max: Option<f64>) -> Result<(), GridError> {
+        let col_idx = self.column_idx(column)?;
+        if self.columns[col_idx].data.value_kind() != ValueKind::Number {
+            return Err(GridError::new(ErrorCode::ColumnTypeMismatch, format!("Column is not numeric: {}", column))
+                .on_column(column));
+        }
+
+        let filters = &mut self.view.range_filters;
+        filters.retain(|f| f.column != col_idx);
+        filters.push(RangeFilter { column: col_idx, min, max });
+        self.view.invalidate();
+        Ok(())
+    }
+
+    /// Remove the range filter on a column, or all range filters if omitted
+    /// An unknown column throws (code UNKNOWN_COLUMN) and clears nothing.
+    #[wasm_bindgen(js_name = clearRangeFilter)]
+    pub fn clear_range_filter(&mut self, column: Option<String>) -> Result<(), GridError> {
+        let before = self.view.range_filters.len();
+        match column {
+            Some(name) => {
+                let col_idx = self.column_idx(&name)?;
+                self.view.range_filters.retain(|f| f.column != col_idx);
+            }
+            None => self.view.range_filters.clear(),
+        }
+        if self.view.range_filters.len() != before {
+            self.view.invalidate();
+        }
+        Ok(())
+    }
+
+    /// Clear sort
+    #[wasm_bindgen(js_name = clearSort)]
+    pub fn clear_sort(&mut self) {
+        if self.view.sort_column.is_some() {
+            self.view.sort_column = None;
+            self.view.sort_dir = SortDir::None;
+            self.view.invalidate();
+        }
+    }
+
+    /// Get number of rows in current view (after filter)
+    #[wasm_bindgen(js_name = viewCount)]
+    pub fn view_count(&mut self) -> usize {
+        self.ensure_view();
+        self.view.cached_view.as_ref().map(|v| v.len()).unwrap_or(0)
+    }
+
+    /// Get total row count (before filter)
+    #[wasm_bindgen(js_name = rowCount)]
+    pub fn row_count(&self) -> usize {
+        self.row_count - self.deleted.iter().filter(|&&d| d).count()
+    }
+
+    /// Get view indices for virtualized rendering
+    #[wasm_bindgen(js_name = viewIndices)]
+    pub fn view_indices(&mut self, start: usize, count: usize) -> Uint32Array {
+        self.ensure_view();
+
+        let view = self.view.cached_view.as_ref().unwrap();
+        let end = (start + count).min(view.len());
+        Uint32Array::from(&view[start.min(end)..end])
+    }
+
+    /// Rows of a selection that aren't deleted, in the given order
+    /// Lets chunked exports size and snapshot a selection up front.
+    #[wasm_bindgen(js_name = liveRows)]
+    pub fn live_rows(&self, rows: &JsValue) -> Result<Uint32Array, GridError> {
+        let rows = self.export_rows(rows)?.unwrap_or_default();
+        Ok(Uint32Array::from(&rows[..]))
+    }
+
+    // ------------------------------------------------------------------------
+    // Zero-copy access
+    //
+    // These expose buffers that live in WASM linear memory. Invalidation rules:
+    // 1. Any mutating call (load/insert/update/delete/setFilter/setSort/...)
+    //    may reallocate or drop the buffer - re-read ptr/len afterwards.
+    // 2. Any allocation inside WASM may grow memory, which detaches every
+    //    ArrayBuffer previously taken from `wasmMemory().buffer`.
+    // Build the typed array right before reading it and never keep it across
+    // calls into the store.
+    // ------------------------------------------------------------------------
+
+    /// Pointer (byte offset into WASM memory) of the cached view indices
+    #[wasm_bindgen(js_name = viewPtr)]
+    pub fn view_ptr(&mut self) -> usize {
+        self.ensure_view();
+        self.view.cached_view.as_ref().unwrap().as_ptr() as usize
+    }
+
+    /// Number of u32 entries at `viewPtr()`
+    #[wasm_bindgen(js_name = viewLen)]
+    pub fn view_len(&mut self) -> usize {
+        self.view_count()
+    }
+
+    /// Uint32Array view over the cached view indices - no copy
+    /// Same invalidation rules as viewPtr(); do not hold across store calls.
+    #[wasm_bindgen(js_name = viewIndicesView)]
+    pub fn view_indices_view(&mut self) -> Uint32Array {
+        self.ensure_view();
+        let view = self.view.cached_view.as_ref().unwrap();
+        // SAFETY: the view lives until the next &mut call on the store, and the
+        // JS side is documented to drop the array before making one.
+        unsafe { Uint32Array::view(view) }
+    }
+
+    /// Pointer to a numeric column's f64 buffer (indexed by row, NaN = null)
+    /// Length is `columnLen()`; deleted rows are still present in the buffer.
+    #[wasm_bindgen(js_name = numberColumnPtr)]
+    pub fn number_column_ptr(&self, column: &str) -> Result<usize, GridError> {
+        Ok(self.number_column(column)?.as_ptr() as usize)
+    }
+
+    /// Float64Array view over a numeric column - no copy
+    #[wasm_bindgen(js_name = numberColumnView)]
+    pub fn number_column_view(&self, column: &str) -> Result<Float64Array, GridError> {
+        let values = self.number_column(column)?;
+        // SAFETY: see viewIndicesView()
+        Ok(unsafe { Float64Array::view(values) })
+    }
+
+    /// Physical length of every column buffer (includes soft-deleted rows)
+    #[wasm_bindgen(js_name = columnLen)]
+    pub fn column_len(&self) -> usize {
+        self.row_count
+    }
+
+    /// Get rows by indices - returns JSON array
+    #[wasm_bindgen(js_name = getRows)]
+    pub fn get_rows(&self, indices: &Uint32Array) -> JsValue {
+        let result = Array::new();
+
+        for i in 0..indices.length() {
+            let row_idx = indices.get_index(i) as usize;
+            let row_obj = self.row_to_js(row_idx);
+            result.push(&row_obj);
+        }
+
+        result.into()
+    }
+
+    /// Get visible rows for rendering (combines viewIndices + getRows)
+    #[wasm_bindgen(js_name = getVisibleRows)]
+    pub fn get_visible_rows(&mut self, start: usize, count: usize) -> JsValue {
+        self.ensure_view();
+
+        let view = self.view.cached_view.as_ref().unwrap();
+        let end = (start + count).min(view.len());
+
+        let result = Array::new();
+        for &row_idx in &view[start..end] {
+            let row_obj = self.row_to_js(row_idx as usize);
+            result.push(&row_obj);
+        }
+
+        result.into()
+    }
+
+    /// Match highlight ranges for the visible rows
+    /// Computed with the same normalization as the filter, so the highlighted
+    /// text is exactly what made the row match. One entry per visible row:
+    /// [{ column, ranges: [[start, end], ...] }, ...] - offsets are UTF-16
+    /// code units (JS string indices), end exclusive.
+    #[wasm_bindgen(js_name = getVisibleMatches)]
+    pub fn get_visible_matches(&mut self, start: usize, count: usize) -> JsValue {
+        self.ensure_view();
+
+        let view = self.view.cached_view.as_ref().unwrap();
+        let end = (start + count).min(view.len());
+
+        let result = Array::new();
+        for &row_idx in &view[start.min(end)..end] {
+            let row_matches = Array::new();
+            for &col_idx in &self.indexed_columns {
+                let ranges = self.match_ranges(row_idx as usize, col_idx);
+                if ranges.is_empty() {
+                    continue;
+                }
+
+                let js_ranges = Array::new();
+                for (range_start, range_end) in ranges {
+                    js_ranges.push(&Array::of2(&JsValue::from(range_start), &JsValue::from(range_end)));
+                }
+                let entry = Object::new();
+                Reflect::set(&entry, &JsValue::from_str("column"), &JsValue::from_str(&self.columns[col_idx].name)).unwrap();
+                Reflect::set(&entry, &JsValue::from_str("ranges"), &js_ranges).unwrap();
+                row_matches.push(&entry);
+            }
+            result.push(&row_matches);
+        }
+
+        result.into()
+    }
+
+    /// Get visible rows in columnar form (one typed array per column)
+    /// Avoids building a JS object per row - the renderer reads columns directly.
+    /// Returns { [column]: Float64Array | { values: Int32Array, nulls: Uint8Array }
+    ///   | { codes: Int32Array, values: string[] } } - one shape per column type
+    #[wasm_bindgen(js_name = getVisibleColumns)]
+    pub fn get_visible_columns(&mut self, start: usize, count: usize, columns: &JsValue) -> Result<JsValue, GridError> {
+        let names = Array::from(columns);
+        let mut requested = Vec::with_capacity(names.length() as usize);
+        for i in 0..names.length() {
+            let name = names.get(i)
+                .as_string()
+                .ok_or_else(|| GridError::new(ErrorCode::UnknownColumn, "Column name must be string"))?;
+            let col_idx = *self.column_index.get(&name)
+                .ok_or_else(|| GridError::new(ErrorCode::UnknownColumn, format!("Unknown column: {}", name)).on_column(name.clone()))?;
+            requested.push((name, col_idx));
+        }
+
+        self.ensure_view();
+
+        let view = self.view.cached_view.as_ref().unwrap();
+        let end = (start + count).min(view.len());
+        let rows = &view[start.min(end)..end];
+
+        let result = Object::new();
+        for (name, col_idx) in requested {
+            let col = &self.columns[col_idx];
+            let value = col.data.gather_js(rows, col.col_type == ColumnType::Integer);
+            Reflect::set(&result, &JsValue::from_str(&name), &value).unwrap();
+        }
+
+        Ok(result.into())
+    }
+
+    /// Row indices whose `column` equals `value`, ascending
+    /// O(matches) with a secondary index, O(n) scan without
+    #[wasm_bindgen(js_name = findRows)]
+    pub fn find_rows(&self, column: &str, value: &JsValue) -> Result<Uint32Array, GridError> {
+        let col = self.column(column)?;
+        let key = col.key_from_js(value)?;
+
+        let rows = match &col.secondary {
+            Some(index) => index.get(&key),
+            None => self.scan_rows(col, |row| col.data.cmp_key(row, &key) == Ordering::Equal),
+        };
+        Ok(Uint32Array::from(&rows[..]))
+    }
+
+    /// Row indices with `min <= column <= max`, ascending
+    /// null/undefined leaves that side open; a bound of the wrong type is a
+    /// ColumnTypeMismatch. Nulls never match a numeric range.
+    /// O(log n + matches) with a sorted index (built on first use for numeric
+    /// columns), O(n) scan otherwise.
+    #[wasm_bindgen(js_name = findRowsInRange)]
+    pub fn find_rows_in_range(&mut self, column: &str, min: &JsValue, max: &JsValue) -> Result<Uint32Array, GridError> {
+        self.ensure_sorted_index(self.column_idx(column)?);
+        let col = self.column(column)?;
+        let bound = |v: &JsValue| -> Result<Option<IndexKey>, GridError> {
+            if v.is_undefined() || v.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(col.key_from_js(v)?).filter(|key| *key != IndexKey::Null))
+        };
+        let (min_key, max_key) = (bound(min)?, bound(max)?);
+
+        let lower = match &min_key {
+            Some(key) => Bound::Included(key),
+            None if col.data.value_kind() == ValueKind::Number => Bound::Excluded(&IndexKey::Null),
+            None => Bound::Unbounded,
+        };
+        let upper = max_key.as_ref().map_or(Bound::Unbounded, Bound::Included);
+
+        let indexed = col.secondary.as_ref().and_then(|index| index.range(lower, upper));
+        let rows = match indexed {
+            Some(mut rows) => {
+                rows.sort_unstable();
+                rows
+            }
+            None => self.scan_rows(col, |row| {
+                let above = match lower {
+                    Bound::Included(k) => col.data.cmp_key(row, k) != Ordering::Less,
+                    Bound::Excluded(k) => col.data.cmp_key(row, k) == Ordering::Greater,
+                    Bound::Unbounded => true,
+                };
+                above && match upper {
+                    Bound::Included(k) => col.data.cmp_key(row, k) != Ordering::Greater,
+                    Bound::Excluded(k) => col.data.cmp_key(row, k) == Ordering::Less,
+                    Bound::Unbounded => true,
+                }
+            }),
+        };
+        Ok(Uint32Array::from(&rows[..]))
+    }
+
+    /// First `count` rows by a column's value, ignoring filters and nulls (NaN, or "" in string columns)
+    /// O(log n + count) from the column's sorted index; columns without one
+    /// (strings, hash-indexed) fall back to a full sort.
+    #[wasm_bindgen(js_name = topN)]
+    pub fn top_n(&mut self, column: &str, count: usize, direction: SortDir) -> Result<Uint32Array, GridError> {
+        let col_idx = self.column_idx(column)?;
+        let rows = self.top_rows(col_idx, count, direction);
+        Ok(Uint32Array::from(&rows[..]))
+    }
+
+    /// Get a single cell value
+    #[wasm_bindgen(js_name = getCell)]
+    pub fn get_cell(&self, row: u32, column: &str) -> JsValue {
+        if let Some(&col_idx) = self.column_index.get(column) {
+            self.columns[col_idx].data.to_js_value(row as usize)
+        } else {
+            JsValue::UNDEFINED
+        }
+    }
+
+    /// Drop empty trigram posting lists left behind by updates/deletes
+    /// Also runs automatically once empty lists pile up. Returns lists removed.
+    #[wasm_bindgen(js_name = compactTrigramIndex)]
+    pub fn compact_trigram_index(&mut self) -> usize {
+        self.trigram_index.prune()
+    }
+
+    /// Trigram index statistics for memory budgeting
+    /// Returns { trigrams, shortGrams, emptyLists, postings, estimatedBytes, largest: [{ trigram, rows }] }
+    /// `top` limits the largest-lists report (default 10)
+    #[wasm_bindgen(js_name = trigramStats)]
+    pub fn trigram_stats(&self, top: Option<usize>) -> JsValue {
+        let stats = self.trigram_index.stats(top.unwrap_or(10));
+
+        let largest = Array::new();
+        for (trigram, rows) in &stats.largest {
+            let entry = Object::new();
+            Reflect::set(&entry, &JsValue::from_str("trigram"),
+                &JsValue::from_str(&trigram.iter().collect::<String>())).unwrap();
+            Reflect::set(&entry, &JsValue::from_str("rows"), &JsValue::from_f64(*rows as f64)).unwrap();
+            largest.push(&entry);
+        }
+
+        let obj = Object::new();
+        Reflect::set(&obj, &JsValue::from_str("trigrams"), &JsValue::from_f64(stats.trigrams as f64)).unwrap();
+        Reflect::set(&obj, &JsValue::from_str("shortGrams"), &JsValue::from_f64(stats.short_grams as f64)).unwrap();
+        Reflect::set(&obj, &JsValue::from_str("emptyLists"), &JsValue::from_f64(stats.empty_lists as f64)).unwrap();
+        Reflect::set(&obj, &JsValue::from_str("postings"), &JsValue::from_f64(stats.postings as f64)).unwrap();
+        Reflect::set(&obj, &JsValue::from_str("estimatedBytes"), &JsValue::from_f64(stats.estimated_bytes as f64)).unwrap();
+        Reflect::set(&obj, &JsValue::from_str("largest"), &largest).unwrap();
+        obj.into()
+    }
+
+    // ------------------------------------------------------------------------
+    // Schema evolution
+    // ------------------------------------------------------------------------
+
+    /// Add a column to a live store - O(n), plus re-indexing text if `indexed`
+    /// Takes a schema entry with an optional `default` (coerced like any
+    /// cell); existing rows get the default, or null without one.
+    #[wasm_bindgen(js_name = addColumn)]
+    pub fn add_column(&mut self, def: &JsValue) -> Result<(), GridError> {
+        let (mut column, is_primary) = Column::from_def(def, self.default_coercion)?;
+        if self.column_index.contains_key(&column.name) {
+            return Err(GridError::new(ErrorCode::DuplicateColumn, format!("Duplicate column name: {}", column.name))
+                .on_column(column.name));
+        }
+        if is_primary {
+            return Err(GridError::new(ErrorCode::PrimaryKeyColumn, "Can't add a primary key column to a live store")
+                .on_column(column.name));
+        }
+
+        // Backfill every physical row, deleted ones included
+        let default = Reflect::get(def, &JsValue::from_str("default")).unwrap_or(JsValue::UNDEFINED);
+        let invalid_default = || {
+            GridError::new(ErrorCode::ColumnTypeMismatch, format!("Invalid default for column '{}'", column.name))
+                .on_column(column.name.clone())
+        };
+        column.data = match column.data {
+            ColumnData::Strings(_) => {
+                let value = column.coercion.string(&default).map_err(|_| invalid_default())?;
+                ColumnData::Strings(vec![value; self.row_count])
+            }
+            ColumnData::Numbers(_) => {
+                let value = column.coercion.number(&default, column.col_type).map_err(|_| invalid_default())?;
+                ColumnData::Numbers(vec![value; self.row_count])
+            }
+        };
+
+        if let Some(index) = &mut column.secondary {
+            for row in (0..self.row_count).filter(|&row| !self.deleted[row]) {
+                index.insert(column.data.index_key(row), row as u32);
+            }
+        }
+
+        let col_idx = self.columns.len();
+        let indexed = column.indexed;
+        self.column_index.insert(column.name.clone(), col_idx);
+        self.columns.push(column);
+        if indexed {
+            self.reindex_text(|store| store.indexed_columns.push(col_idx));
+        }
+
+        self.view.invalidate();
+        Ok(())
+    }
+
+    /// Remove a column and its data - O(cols), plus re-indexing text if it was indexed
+    /// Primary key columns can't be dropped.
+    #[wasm_bindgen(js_name = dropColumn)]
+    pub fn drop_column(&mut self, name: &str) -> Result<(), GridError> {
+        let col_idx = self.column_idx(name)?;
+        if self.key_columns.contains(&col_idx) {
+            return Err(GridError::new(ErrorCode::PrimaryKeyColumn, format!("Can't drop primary key column '{}'", name))
+                .on_column(name));
+        }
+
+        if self.columns[col_idx].indexed {
+            self.reindex_text(|store| store.indexed_columns.retain(|&c| c != col_idx));
+        }
+        self.columns.remove(col_idx);
+
+        // Shift every column position after the dropped one
+        let shift = |c: &mut usize| {
+            if *c > col_idx {
+                *c -= 1;
+            }
+        };
+        self.column_index.remove(name);
+        self.column_index.values_mut().for_each(shift);
+        self.indexed_columns.iter_mut().for_each(shift);
+        self.key_columns.iter_mut().for_each(shift);
+
+        let view = &mut self.view;
+        view.range_filters.retain(|f| f.column != col_idx);
+        view.range_filters.iter_mut().for_each(|f| shift(&mut f.column));
+        if view.sort_column == Some(col_idx) {
+            view.sort_column = None;
+            view.sort_dir = SortDir::None;
+        }
+        if let Some(c) = &mut view.sort_column {
+            shift(c);
+        }
+
+        self.view.invalidate();
+        Ok(())
+    }
+
+    /// Include a column in (or exclude it from) text search
+    /// Rows keep their postings for other columns - only the grams this
+    /// column contributes are added or removed.
+    #[wasm_bindgen(js_name = setColumnIndexed)]
+    pub fn set_column_indexed(&mut self, name: &str, indexed: bool) -> Result<(), GridError> {
+        let col_idx = self.column_idx(name)?;
+        if self.columns[col_idx].indexed == indexed {
+            return Ok(());
+        }
+
+        self.columns[col_idx].indexed = indexed;
+        self.reindex_text(|store| {
+            if indexed {
+                // Keep schema order so the indexed text is laid out consistently
+                let pos = store.indexed_columns.partition_point(|&c| c < col_idx);
+                store.indexed_columns.insert(pos, col_idx);
+            } else {
+                store.indexed_columns.retain(|&c| c != col_idx);
+            }
+        });
+
+        self.view.invalidate();
+        Ok(())
+    }
+
+    /// Drain values that failed type coercion since the last call
+    /// Returns { errors: [{ key, column, value, expected, policy }], dropped }
+    /// where `dropped` counts failures beyond the log's capacity.
+    #[wasm_bindgen(js_name = takeCoercionErrors)]
+    pub fn take_coercion_errors(&mut self) -> JsValue {
+        self.coercion_errors.take_js()
+    }
+
+    /// Save the store as a versioned binary snapshot - O(data size)
+    /// Filter/sort state and the coercion log are not included. Pass
+    /// includeIndex to store the trigram index too, so restoring skips the
+    /// rebuild at the cost of a larger snapshot.
+    pub fn serialize(&self, include_index: Option<bool>) -> Vec<u8> {
+        snapshot::write(self, include_index.unwrap_or(false))
+    }
+
+    /// Restore a store saved by serialize()
+    /// Snapshots from another format version are rejected with
+    /// UNSUPPORTED_SNAPSHOT_VERSION; damaged ones with INVALID_SNAPSHOT.
+    pub fn deserialize(bytes: &[u8]) -> Result<GridStore, GridError> {
+        snapshot::read(bytes)
+    }
+
+    /// Full column definitions in schema order:
+    /// [{ name, type, primaryKey, indexed, index?, nullable, collation, coercion,
+    ///    format?, units?, meta? }, ...]
+    /// Every column of a composite key has primaryKey: true plus its keyPosition.
+    pub fn schema(&self) -> JsValue {
+        let composite = self.key_columns.len() > 1;
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(col_idx, col)| {
+                let key_position = self.key_columns.iter().position(|&c| c == col_idx);
+                col.to_def(key_position, composite)
+            })
+            .collect::<Array>()
+            .into()
+    }
+
+    /// Get column names
+    #[wasm_bindgen(js_name = columnNames)]
+    pub fn column_names(&self) -> JsValue {
+        let arr = Array::new();
+        for col in &self.columns {
+            arr.push(&JsValue::from_str(&col.name));
+        }
+        arr.into()
+    }
+}
+
+// Private implementation
+impl GridStore {
+    fn insert_row_internal(&mut self, row: &JsValue) -> Result<u32, GridError> {
+        // Extract key
+        let key = self.key_from_object(row)?;
+
+        // Check for duplicate
+        if self.id_to_row.contains_key(&key) {
+            return Err(GridError::new(ErrorCode::DuplicateKey, format!("Duplicate ID: {}", key)));
+        }
+
+        // Insert values into columns - cells failing coercion are stored as null
+        // (lenient integer cells as their rounded value)
+        for col_idx in 0..self.columns.len() {
+            let col = &mut self.columns[col_idx];
+            let value = Reflect::get(row, &JsValue::from_str(&col.name))
+                .unwrap_or(JsValue::NULL);
+
+            let failed = match &mut col.data {
+                ColumnData::Strings(v) => {
+                    let s = col.coercion.string(&value);
+                    let failed = s.is_err();
+                    v.push(s.unwrap_or_default());
+                    failed
+                }
+                ColumnData::Numbers(v) => {
+                    let (n, failed) = match col.coercion.number(&value, col.col_type) {
+                        Ok(n) => (n, false),
+                        Err(CellError::Rounded(n)) => (n, true),
+                        Err(CellError::Rejected) => (f64::NAN, true),
+                    };
+                    v.push(n);
+                    failed
+                }
+            };
+            if failed {
+                self.log_coercion(key.clone(), col_idx, value);
+            }
+        }
+
+        Ok(self.commit_insert(key))
+    }
+
+    /// Insert a decoded row - `cells[i]` is column i, `None` = null
+    fn insert_cells(&mut self, cells: &[Option<Value>]) -> Result<u32, GridError> {
+        let key = self.key_from_cells(cells)?;
+
+        if self.id_to_row.contains_key(&key) {
+            return Err(GridError::new(ErrorCode::DuplicateKey, format!("Duplicate ID: {}", key)));
+        }
+
+        let mut failed = Vec::new();
+        for (col_idx, (col, cell)) in self.columns.iter_mut().zip(cells).enumerate() {
+            let checked = match cell {
+                Some(Value::Number(n)) => Some(col.check_number(*n)),
+                _ => None,
+            };
+            match (&mut col.data, cell, checked) {
+                (ColumnData::Strings(v), Some(Value::String(s)), _) => v.push(s.to_string()),
+                (ColumnData::Numbers(v), _, Some(Ok(n))) => v.push(n),
+                (ColumnData::Numbers(v), Some(Value::Number(n)), Some(Err(e))) => {
+                    failed.push((col_idx, *n));
+                    v.push(match e {
+                        CellError::Rounded(rounded) => rounded,
+                        CellError::Rejected => f64::NAN,
+                    });
+                }
+                (data, _, _) => data.push_null(),
+            }
+        }
+        for (col_idx, n) in failed {
+            self.log_coercion(key.clone(), col_idx, JsValue::from_f64(n));
+        }
+
+        Ok(self.commit_insert(key))
+    }
+
+    /// Register a row whose column values were just pushed
+    /// Does not touch the trigram index - callers follow up with index_rows_from()
+    fn commit_insert(&mut self, key: RowKey) -> u32 {
+        let row_idx = self.row_count as u32;
+
+        // Add to key index
+        self.id_to_row.insert(key, row_idx);
+        self.deleted.push(false);
+        self.row_count += 1;
+
+        self.add_to_secondary(row_idx);
+
+        row_idx
+    }
+
+    /// Key part for a key column value - strings as-is, numbers must be integers
+    fn key_part_js(&self, col_idx: usize, value: &JsValue) -> Result<RowKey, GridError> {
+        let col = &self.columns[col_idx];
+        match (col.data.value_kind(), value.as_f64()) {
+            (ValueKind::String, _) => value.as_string().map(RowKey::Str).ok_or_else(|| self.key_error(col_idx, value)),
+            (ValueKind::Number, Some(n)) => self.number_key_part(col_idx, n),
+            (ValueKind::Number, None) => Err(self.key_error(col_idx, value)),
+        }
+    }
+
+    /// Key part from stored (or about-to-be-stored) column data
+    fn key_part(&self, col_idx: usize, data: &ColumnData, row: usize) -> Result<RowKey, GridError> {
+        match data {
+            ColumnData::Strings(v) => Ok(RowKey::Str(v[row].clone())),
+            ColumnData::Numbers(v) => self.number_key_part(col_idx, v[row]),
+        }
+    }
+
+    /// Key part for a number - a safe integer, and within 32 bits in integer
+    /// columns whose coercion policy checks the range (strict, lenient)
+    fn number_key_part(&self, col_idx: usize, n: f64) -> Result<RowKey, GridError> {
+        let col = &self.columns[col_idx];
+        let in_range = col.col_type != ColumnType::Integer || col.coercion.integer(n).is_ok();
+        RowKey::int(n)
+            .filter(|_| in_range)
+            .ok_or_else(|| self.number_key_error(col_idx, n))
+    }
+
+    /// Error for a missing (null/undefined) or invalid key part, naming what
+    /// the key column expects and the value it got
+    fn key_error(&self, col_idx: usize, value: &JsValue) -> GridError {
+        if value.is_undefined() || value.is_null() {
+            return self.missing_key_error(col_idx);
+        }
+        self.invalid_key_error(col_idx, value)
+    }
+
+    /// key_error for a numeric cell - NaN is a missing key
+    fn number_key_error(&self, col_idx: usize, n: f64) -> GridError {
+        if n.is_nan() {
+            return self.missing_key_error(col_idx);
+        }
+        self.invalid_key_error(col_idx, &n)
+    }
+
+    fn missing_key_error(&self, col_idx: usize) -> GridError {
+        GridError::new(ErrorCode::MissingKey, "Row must have ID field").on_column(self.columns[col_idx].name.clone())
+    }
+
+    fn invalid_key_error(&self, col_idx: usize, value: &dyn std::fmt::Debug) -> GridError {
+        let col = &self.columns[col_idx];
+        let expected = match col.data.value_kind() {
+            ValueKind::String => "a string",
+            ValueKind::Number if col.col_type == ColumnType::Integer && col.coercion != CoercionPolicy::Legacy => {
+                "an integer from -2147483648 to 2147483647"
+            }
+            ValueKind::Number => "an integer",
+        };
+        let message = if self.key_columns.len() > 1 {
+            format!("Composite primary key part '{}' must be {}, got {:?}", col.name, expected, value)
+        } else {
+            format!("Primary key '{}' must be {}, got {:?}", col.name, expected, value)
+        };
+        GridError::new(ErrorCode::InvalidKey, message).on_column(col.name.clone())
+    }
+
+    /// Key of a row object (or an object holding just the key columns)
+    fn key_from_object(&self, obj: &JsValue) -> Result<RowKey, GridError> {
+        let parts = self.key_columns
+            .iter()
+            .map(|&col_idx| {
+                let value = Reflect::get(obj, &JsValue::from_str(&self.columns[col_idx].name))
+                    .unwrap_or(JsValue::UNDEFINED);
+                self.key_part_js(col_idx, &value)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(RowKey::from_parts(parts))
+    }
+
+    /// Key passed to update/delete: a value, an array of parts, or an object
+    fn key_from_js(&self, key: &JsValue) -> Result<RowKey, GridError> {
+        if Array::is_array(key) {
+            let parts = Array::from(key);
+            if parts.length() as usize != self.key_columns.len() {
+                return Err(GridError::new(
+                    ErrorCode::InvalidKey,
+                    format!("Key has {} parts, expected {}", parts.length(), self.key_columns.len()),
+                ));
+            }
+            let parts = self.key_columns
+                .iter()
+                .zip(parts.iter())
+                .map(|(&col_idx, part)| self.key_part_js(col_idx, &part))
+                .collect::<Result<_, _>>()?;
+            return Ok(RowKey::from_parts(parts));
+        }
+        if key.is_object() {
+            return self.key_from_object(key);
+        }
+        match self.key_columns[..] {
+            [col_idx] => self.key_part_js(col_idx, key),
+            _ => Err(GridError::new(ErrorCode::InvalidKey, "Composite key must be an array or object")),
+        }
+    }
+
+    /// Key of a decoded binary row
+    fn key_from_cells(&self, cells: &[Option<Value>]) -> Result<RowKey, GridError> {
+        let parts = self.key_columns
+            .iter()
+            .map(|&col_idx| match cells[col_idx] {
+                Some(Value::String(s)) => Ok(RowKey::Str(s.to_string())),
+                Some(Value::Number(n)) => self.number_key_part(col_idx, n),
+                None => Err(self.key_error(col_idx, &JsValue::UNDEFINED)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(RowKey::from_parts(parts))
+    }
+
+    /// Apply a change to the indexed column set, then move every live row's
+    /// trigram postings from its old text to its new text
+    /// Each row's grams are diffed, so only postings for the text the changed
+    /// column adds or removes are touched. Numeric columns contribute no text,
+    /// so a change to them skips the pass entirely.
+    fn reindex_text(&mut self, change: impl FnOnce(&mut Self)) {
+        let before = self.indexed_columns.clone();
+        change(self);
+        let changed_text = before.iter()
+            .chain(&self.indexed_columns)
+            .filter(|&&c| before.contains(&c) != self.indexed_columns.contains(&c))
+            .any(|&c| self.columns[c].data.value_kind() == ValueKind::String);
+        if !changed_text {
+            return;
+        }
+
+        let after = std::mem::replace(&mut self.indexed_columns, before);
+        let old_texts: Vec<String> = (0..self.row_count)
+            .map(|row| if self.deleted[row] { String::new() } else { self.get_indexed_text(row) })
+            .collect();
+        self.indexed_columns = after;
+
+        for (row, old_text) in old_texts.iter().enumerate() {
+            if !self.deleted[row] {
+                let new_text = self.get_indexed_text(row);
+                self.trigram_index.update(row as u32, old_text, &new_text);
+            }
+        }
+    }
+
+    /// Append `count` rows of typed column data - `None` columns are null
+    /// Keys are validated before anything is stored. `failures` are
+    /// (column, row, value) coercion failures to log once keys are known.
+    fn append_columns(
+        &mut self,
+        loaded: Vec<Option<ColumnData>>,
+        count: usize,
+        failures: Vec<(usize, usize, JsValue)>,
+    ) -> Result<u32, GridError> {
+        if count == 0 {
+            return Ok(0);
+        }
+
+        // Validate keys before touching the store
+        let mut keys = Vec::with_capacity(count);
+        let mut seen = HashSet::with_capacity(count);
+        for row in 0..count {
+            let parts = self.key_columns
+                .iter()
+                .map(|&col_idx| {
+                    let data = loaded[col_idx].as_ref()
+                        .ok_or_else(|| self.key_error(col_idx, &JsValue::UNDEFINED))?;
+                    self.key_part(col_idx, data, row)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let key = RowKey::from_parts(parts);
+            if self.id_to_row.contains_key(&key) || !seen.insert(key.clone()) {
+                return Err(GridError::new(ErrorCode::DuplicateKey, format!("Duplicate ID: {}", key)));
+            }
+            keys.push(key);
+        }
+
+        for (col_idx, row, value) in failures {
+            self.log_coercion(keys[row].clone(), col_idx, value);
+        }
+
+        let first_row = self.row_count as u32;
+        self.reserve_rows(count);
+        self.id_to_row.reserve(count);
+        for (i, key) in keys.into_iter().enumerate() {
+            self.id_to_row.insert(key, first_row + i as u32);
+        }
+
+        for (col, data) in self.columns.iter_mut().zip(loaded) {
+            match (&mut col.data, data) {
+                (ColumnData::Strings(v), Some(ColumnData::Strings(new))) => v.extend(new),
+                (ColumnData::Numbers(v), Some(ColumnData::Numbers(new))) => v.extend(new),
+                (existing, _) => {
+                    for _ in 0..count {
+                        existing.push_null();
+                    }
+                }
+            }
+        }
+
+        self.deleted.resize(self.row_count + count, false);
+        self.row_count += count;
+
+        for row in first_row..self.row_count as u32 {
+            self.add_to_secondary(row);
+        }
+        self.index_rows_from(first_row);
+
+        self.view.invalidate();
+        Ok(count as u32)
+    }
+
+    /// Add a live row's cells to every secondary index
+    fn add_to_secondary(&mut self, row: u32) {
+        for col in &mut self.columns {
+            if let Some(index) = &mut col.secondary {
+                index.insert(col.data.index_key(row as usize), row);
+            }
         }
     }
 
-    /// Get number of rows in current view (after filter)
-    #[wasm_bindgen(js_name = viewCount)]
-    pub fn view_count(&mut self) -> usize {
-        self.ensure_view();
-        self.view.cached_view.as_ref().map(|v| v.len()).unwrap_or(0)
+    /// Every cell write goes through here so secondary indexes stay in sync
+    fn write_cell(&mut self, row_idx: usize, col_idx: usize, write: impl FnOnce(&mut ColumnData)) {
+        let col = &mut self.columns[col_idx];
+        match &mut col.secondary {
+            Some(index) if !self.deleted[row_idx] => {
+                let old_key = col.data.index_key(row_idx);
+                write(&mut col.data);
+                let new_key = col.data.index_key(row_idx);
+                if new_key != old_key {
+                    index.remove(&old_key, row_idx as u32);
+                    index.insert(new_key, row_idx as u32);
+                }
+            }
+            _ => write(&mut col.data),
+        }
     }
 
-    /// Get total row count (before filter)
-    #[wasm_bindgen(js_name = rowCount)]
-    pub fn row_count(&self) -> usize {
-        self.row_count - self.deleted.iter().filter(|&&d| d).count()
+    /// Rebuild the trigram index from scratch over all live rows
+    fn rebuild_text_index(&mut self) {
+        self.trigram_index.clear();
+        self.index_rows(0..self.row_count as u32);
     }
 
-    /// Get view indices for virtualized rendering
-    #[wasm_bindgen(js_name = viewIndices)]
-    pub fn view_indices(&mut self, start: usize, count: usize) -> Uint32Array {
-        self.ensure_view();
-
-        let view = self.view.cached_view.as_ref().unwrap();
-        let end = (start + count).min(view.len());
-        let slice = &view[start..end];
-
-        let arr = Uint32Array::new_with_length(slice.len() as u32);
-        for (i, &idx) in slice.iter().enumerate() {
-            arr.set_index(i as u32, idx);
+    /// Add rows [first_row, row_count) to the trigram index
+    fn index_rows_from(&mut self, first_row: u32) {
+        let end = self.row_count as u32;
+        if end - first_row == 1 {
+            let indexed_text = self.get_indexed_text(first_row as usize);
+            self.trigram_index.add(first_row, &indexed_text);
+        } else if end > first_row {
+            self.index_rows(first_row..end);
         }
-        arr
     }
 
-    /// Get rows by indices - returns JSON array
-    #[wasm_bindgen(js_name = getRows)]
-    pub fn get_rows(&self, indices: &Uint32Array) -> JsValue {
-        let result = Array::new();
+    /// Bulk-index the live rows of a range, BULK_INDEX_ROWS at a time
+    /// Chunks ascend, so each one appends to the posting lists in order.
+    fn index_rows(&mut self, rows: std::ops::Range<u32>) {
+        for start in rows.clone().step_by(BULK_INDEX_ROWS) {
+            let end = start.saturating_add(BULK_INDEX_ROWS as u32).min(rows.end);
+            let texts: Vec<(u32, String)> = (start..end)
+                .filter(|&row| !self.deleted[row as usize])
+                .map(|row| (row, self.get_indexed_text(row as usize)))
+                .collect();
+            self.trigram_index.add_bulk(&texts);
+        }
+    }
 
-        for i in 0..indices.length() {
-            let row_idx = indices.get_index(i) as usize;
-            let row_obj = self.row_to_js(row_idx);
-            result.push(&row_obj);
+    fn reserve_rows(&mut self, additional: usize) {
+        for col in &mut self.columns {
+            match &mut col.data {
+                ColumnData::Strings(v) => v.reserve(additional),
+                ColumnData::Numbers(v) => v.reserve(additional),
+            }
         }
+        self.deleted.reserve(additional);
+    }
 
-        result.into()
+    fn value_kinds(&self) -> Vec<ValueKind> {
+        self.columns.iter().map(|c| c.data.value_kind()).collect()
     }
 
-    /// Get visible rows for rendering (combines viewIndices + getRows)
-    #[wasm_bindgen(js_name = getVisibleRows)]
-    pub fn get_visible_rows(&mut self, start: usize, count: usize) -> JsValue {
-        self.ensure_view();
+    fn column_idx(&self, column: &str) -> Result<usize, GridError> {
+        self.column_index.get(column)
+            .copied()
+            .ok_or_else(|| GridError::new(ErrorCode::UnknownColumn, format!("Unknown column: {}", column)).on_column(column))
+    }
 
-        let view = self.view.cached_view.as_ref().unwrap();
-        let end = (start + count).min(view.len());
+    fn column(&self, column: &str) -> Result<&Column, GridError> {
+        Ok(&self.columns[self.column_idx(column)?])
+    }
 
-        let result = Array::new();
-        for &row_idx in &view[start..end] {
-            let row_obj = self.row_to_js(row_idx as usize);
-            result.push(&row_obj);
+    /// Columns listed in an export's `columns` option, in order - all if absent
+    fn export_columns(&self, columns: &JsValue) -> Result<Vec<usize>, GridError> {
+        if !Array::is_array(columns) {
+            return Ok((0..self.columns.len()).collect());
         }
-
-        result.into()
+        Array::from(columns)
+            .iter()
+            .map(|name| self.column_idx(&name.as_string().unwrap_or_default()))
+            .collect()
     }
 
-    /// Get a single cell value
-    #[wasm_bindgen(js_name = getCell)]
-    pub fn get_cell(&self, row: u32, column: &str) -> JsValue {
-        if let Some(&col_idx) = self.column_index.get(column) {
-            self.columns[col_idx].data.to_js_value(row as usize)
+    /// Row indices from an export's `rows` option, or None to export the view
+    /// Deleted rows can only come from a stale selection and are left out;
+    /// anything but a non-negative integer index is an error.
+    fn export_rows(&self, rows: &JsValue) -> Result<Option<Vec<u32>>, GridError> {
+        if rows.is_undefined() || rows.is_null() {
+            return Ok(None);
+        }
+        let mut rows: Vec<u32> = if rows.is_instance_of::<Uint32Array>() {
+            Uint32Array::from(rows.clone()).to_vec()
         } else {
-            JsValue::UNDEFINED
+            Array::from(rows)
+                .iter()
+                .map(|r| {
+                    r.as_f64()
+                        .filter(|n| n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n))
+                        .map(|n| n as u32)
+                        .ok_or_else(|| {
+                            GridError::new(ErrorCode::RowNotFound, format!("Row index must be a non-negative integer, got {:?}", r))
+                        })
+                })
+                .collect::<Result<_, _>>()?
+        };
+        if let Some(&row) = rows.iter().find(|&&row| row as usize >= self.row_count) {
+            return Err(GridError::new(ErrorCode::RowNotFound, format!("Row not found: {}", row)));
         }
+        rows.retain(|&row| !self.deleted[row as usize]);
+        Ok(Some(rows))
     }
 
-    /// Get column names
-    #[wasm_bindgen(js_name = columnNames)]
-    pub fn column_names(&self) -> JsValue {
-        let arr = Array::new();
-        for col in &self.columns {
-            arr.push(&JsValue::from_str(&col.name));
-        }
-        arr.into()
+    /// Header of an exported column - `headers[name]` if given, else the name
+    fn export_header(&self, headers: &JsValue, col_idx: usize) -> String {
+        let name = &self.columns[col_idx].name;
+        let header = if headers.is_object() {
+            Reflect::get(headers, &JsValue::from_str(name)).ok().and_then(|h| h.as_string())
+        } else {
+            None
+        };
+        header.unwrap_or_else(|| name.clone())
     }
-}
 
-// Private implementation
-impl GridStore {
-    fn insert_row_internal(&mut self, row: &JsValue) -> Result<u32, JsError> {
-        let row_idx = self.row_count as u32;
-
-        // Extract ID
-        let id_col_name = &self.columns[self.id_column].name;
-        let id = Reflect::get(row, &JsValue::from_str(id_col_name))
-            .ok()
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| JsError::new("Row must have ID field"))?;
-
-        // Check for duplicate
-        if self.id_to_row.contains_key(&id) {
-            return Err(JsError::new(&format!("Duplicate ID: {}", id)));
+    /// Build a sorted index on a numeric column if it has none yet - O(n log n) once
+    /// From then on it's maintained incrementally like a declared `index: "sorted"`.
+    /// Declared hash indexes and string columns are left alone.
+    fn ensure_sorted_index(&mut self, col_idx: usize) {
+        let col = &self.columns[col_idx];
+        if col.secondary.is_some() || col.data.value_kind() != ValueKind::Number {
+            return;
         }
+        let entries = (0..self.row_count)
+            .filter(|&row| !self.deleted[row])
+            .map(|row| (col.data.index_key(row), row as u32))
+            .collect();
+        self.columns[col_idx].secondary = Some(SecondaryIndex::sorted_from(entries));
+    }
 
-        // Insert values into columns
-        for col in &mut self.columns {
-            let value = Reflect::get(row, &JsValue::from_str(&col.name))
-                .unwrap_or(JsValue::NULL);
-
-            match &mut col.data {
-                ColumnData::Strings(v) => {
-                    v.push(value.as_string().unwrap_or_default());
-                }
-                ColumnData::Numbers(v) => {
-                    v.push(value.as_f64().unwrap_or(f64::NAN));
-                }
+    fn top_rows(&mut self, col_idx: usize, count: usize, direction: SortDir) -> Vec<u32> {
+        self.ensure_sorted_index(col_idx);
+
+        let col = &self.columns[col_idx];
+        let top = col.ordered_index().and_then(|index| index.top_rows(count, direction == SortDir::Desc));
+        match top {
+            Some(rows) => rows,
+            None => {
+                let live = self.scan_rows(col, |row| !col.data.is_null(row));
+                let dir = if direction == SortDir::Desc { SortDir::Desc } else { SortDir::Asc };
+                let mut rows = self.sorted_rows(live, col_idx, dir);
+                rows.truncate(count);
+                rows
             }
         }
+    }
 
-        // Add to ID index
-        self.id_to_row.insert(id, row_idx);
-        self.deleted.push(false);
-        self.row_count += 1;
-
-        // Add to trigram index
-        let indexed_text = self.get_indexed_text(row_idx as usize);
-        self.trigram_index.add(row_idx, &indexed_text);
+    /// Live rows of `col` passing `pred`, ascending - fallback for unindexed lookups
+    fn scan_rows(&self, col: &Column, pred: impl Fn(usize) -> bool) -> Vec<u32> {
+        (0..col.data.len())
+            .filter(|&row| !self.deleted[row] && pred(row))
+            .map(|row| row as u32)
+            .collect()
+    }
 
-        Ok(row_idx)
+    fn number_column(&self, column: &str) -> Result<&[f64], GridError> {
+        let col_idx = *self.column_index.get(column)
+            .ok_or_else(|| GridError::new(ErrorCode::UnknownColumn, format!("Unknown column: {}", column)).on_column(column))?;
+        match &self.columns[col_idx].data {
+            ColumnData::Numbers(v) => Ok(v),
+            ColumnData::Strings(_) => {
+                Err(GridError::new(ErrorCode::ColumnTypeMismatch, format!("Column is not numeric: {}", column)).on_column(column))
+            }
+        }
     }
 
     fn get_indexed_text(&self, row_idx: usize) -> String {
@@ -600,7 +2525,7 @@ impl GridStore {
         for &col_idx in &self.indexed_columns {
             if let Some(s) = self.columns[col_idx].data.get_string(row_idx) {
                 if !text.is_empty() {
-                    text.push(' ');
+                    text.push(FIELD_SEPARATOR);
                 }
                 text.push_str(s);
             }
@@ -608,20 +2533,80 @@ impl GridStore {
         text
     }
 
-    fn set_cell_value(&mut self, row_idx: usize, col_idx: usize, value: &JsValue) {
-        let col = &mut self.columns[col_idx];
-        match &mut col.data {
-            ColumnData::Strings(v) => {
-                if row_idx < v.len() {
-                    v[row_idx] = value.as_string().unwrap_or_default();
+    /// Write a JS value into a cell under the column's coercion policy
+    /// A failed cell is logged and, except under legacy, left unchanged.
+    fn set_cell_value(&mut self, row_idx: usize, col_idx: usize, value: &JsValue) -> Result<(), GridError> {
+        let col = &self.columns[col_idx];
+        match col.data.value_kind() {
+            ValueKind::String => match col.coercion.string(value) {
+                Ok(s) => {
+                    self.write_cell(row_idx, col_idx, |data| data.set_string(row_idx, &s));
+                    Ok(())
                 }
+                Err(()) => self.reject_cell(row_idx, col_idx, value.clone()),
+            },
+            ValueKind::Number => {
+                let result = col.coercion.number(value, col.col_type);
+                self.set_number_cell(row_idx, col_idx, result, || value.clone())
             }
-            ColumnData::Numbers(v) => {
-                if row_idx < v.len() {
-                    v[row_idx] = value.as_f64().unwrap_or(f64::NAN);
-                }
+        }
+    }
+
+    /// Write a coerced number into a cell - a rounded one is written and
+    /// logged, a rejected one handled as in set_cell_value
+    /// `value` builds the original value for the log, only on failure.
+    fn set_number_cell(
+        &mut self,
+        row_idx: usize,
+        col_idx: usize,
+        result: Result<f64, CellError>,
+        value: impl FnOnce() -> JsValue,
+    ) -> Result<(), GridError> {
+        match result {
+            Ok(n) => {
+                self.write_cell(row_idx, col_idx, |data| data.set_number(row_idx, n));
+                Ok(())
             }
+            Err(CellError::Rounded(n)) => {
+                self.write_cell(row_idx, col_idx, |data| data.set_number(row_idx, n));
+                self.log_coercion(self.row_key(row_idx)?, col_idx, value());
+                Ok(())
+            }
+            Err(CellError::Rejected) => self.reject_cell(row_idx, col_idx, value()),
+        }
+    }
+
+    /// Log a cell that failed coercion - legacy nulls it, the other
+    /// policies leave it unchanged
+    fn reject_cell(&mut self, row_idx: usize, col_idx: usize, value: JsValue) -> Result<(), GridError> {
+        if self.columns[col_idx].coercion.nulls_on_failure() {
+            self.write_cell(row_idx, col_idx, |data| match data {
+                ColumnData::Strings(_) => data.set_string(row_idx, ""),
+                ColumnData::Numbers(_) => data.set_number(row_idx, f64::NAN),
+            });
         }
+        self.log_coercion(self.row_key(row_idx)?, col_idx, value);
+        Ok(())
+    }
+
+    /// Key of a stored row
+    fn row_key(&self, row_idx: usize) -> Result<RowKey, GridError> {
+        let parts = self.key_columns
+            .iter()
+            .map(|&col_idx| self.key_part(col_idx, &self.columns[col_idx].data, row_idx))
+            .collect::<Result<_, _>>()?;
+        Ok(RowKey::from_parts(parts))
+    }
+
+    fn log_coercion(&mut self, key: RowKey, col_idx: usize, value: JsValue) {
+        let col = &self.columns[col_idx];
+        self.coercion_errors.push(CoercionError {
+            key,
+            column: col.name.clone(),
+            value,
+            expected: col.col_type.as_str(),
+            policy: col.coercion,
+        });
     }
 
     fn row_to_js(&self, row_idx: usize) -> JsValue {
@@ -638,77 +2623,377 @@ impl GridStore {
             return;
         }
 
-        let mut indices: Vec<u32> = if self.view.filter_text.is_empty() {
-            // No filter - all non-deleted rows
-            (0..self.row_count as u32)
-                .filter(|&i| !self.deleted[i as usize])
-                .collect()
-        } else {
-            // Use trigram index for candidates
-            let candidates = self.trigram_index.search(&self.view.filter_text);
-
-            if candidates.is_empty() && self.view.filter_text.len() < 3 {
-                // Query too short for trigrams - full scan
-                (0..self.row_count as u32)
-                    .filter(|&i| {
-                        !self.deleted[i as usize] && self.row_matches_filter(i as usize)
-                    })
-                    .collect()
-            } else {
-                // Verify candidates actually match
-                candidates
-                    .into_iter()
-                    .filter(|&i| {
-                        !self.deleted[i as usize] && self.row_matches_filter(i as usize)
-                    })
-                    .collect()
+        // Range filters and numeric sorts are served by sorted indexes
+        for i in 0..self.view.range_filters.len() {
+            self.ensure_sorted_index(self.view.range_filters[i].column);
+        }
+        if let (Some(col_idx), true) = (self.view.sort_column, self.view.sort_dir != SortDir::None) {
+            self.ensure_sorted_index(col_idx);
+        }
+        let range_rows = self.range_rows();
+
+        let fuzzy = self.view.search_mode == SearchMode::Fuzzy && !self.view.filter_terms.is_empty();
+        let fuzzy_rows = fuzzy.then(|| {
+            let mut scored = self.fuzzy_rows();
+            if let Some(range) = &range_rows {
+                scored.retain(|(row, _)| range.binary_search(row).is_ok());
+            }
+            scored
+        });
+        let mut indices = match fuzzy_rows {
+            Some(mut scored) if self.view.rank_by_relevance => {
+                // Best match first, ties keep row order
+                scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                self.view.cached_view = Some(scored.into_iter().map(|(row, _)| row).collect());
+                return;
             }
+            Some(scored) => scored.into_iter().map(|(row, _)| row).collect(),
+            None => self.filtered_rows(range_rows),
         };
 
         // Sort if needed
         if let (Some(col_idx), dir) = (self.view.sort_column, self.view.sort_dir) {
             if dir != SortDir::None {
-                let col = &self.columns[col_idx];
-                match &col.data {
-                    ColumnData::Strings(v) => {
-                        indices.sort_by(|&a, &b| {
-                            let va = &v[a as usize];
-                            let vb = &v[b as usize];
-                            let cmp = va.cmp(vb);
-                            if dir == SortDir::Desc { cmp.reverse() } else { cmp }
-                        });
+                indices = self.sorted_rows(indices, col_idx, dir);
+            }
+        }
+
+        self.view.cached_view = Some(indices);
+    }
+
+    /// Order `indices` by a column - walks the column's sorted index when it
+    /// has one (O(n)), otherwise a full O(k log k) sort
+    fn sorted_rows(&self, mut indices: Vec<u32>, col_idx: usize, dir: SortDir) -> Vec<u32> {
+        let col = &self.columns[col_idx];
+        let ordered = col.ordered_index()
+            // Tiny views sort faster than a walk over the whole index
+            .filter(|_| indices.len() >= self.row_count / 16)
+            .and_then(|index| index.ordered_rows(dir == SortDir::Desc));
+
+        if let Some(mut ordered) = ordered {
+            // The index holds every live row; indices is a subset of them
+            if ordered.len() != indices.len() {
+                let mut keep = vec![false; self.row_count];
+                for &row in &indices {
+                    keep[row as usize] = true;
+                }
+                ordered.retain(|&row| keep[row as usize]);
+            }
+            return ordered;
+        }
+
+        match &col.data {
+            ColumnData::Strings(v) if col.collation == Collation::NoCase => {
+                // Fold once per row rather than per comparison
+                let mut keyed: Vec<(String, u32)> = indices
+                    .iter()
+                    .map(|&row| (v[row as usize].to_lowercase(), row))
+                    .collect();
+                keyed.sort_by(|a, b| {
+                    let cmp = a.0.cmp(&b.0);
+                    if dir == SortDir::Desc { cmp.reverse() } else { cmp }
+                });
+                indices = keyed.into_iter().map(|(_, row)| row).collect();
+            }
+            ColumnData::Strings(v) => {
+                indices.sort_by(|&a, &b| {
+                    let va = &v[a as usize];
+                    let vb = &v[b as usize];
+                    let cmp = va.cmp(vb);
+                    if dir == SortDir::Desc { cmp.reverse() } else { cmp }
+                });
+            }
+            ColumnData::Numbers(v) => {
+                indices.sort_by(|&a, &b| {
+                    // Same total order as the index walk: nulls first, then by value
+                    let cmp = IndexKey::number(v[a as usize]).cmp(&IndexKey::number(v[b as usize]));
+                    if dir == SortDir::Desc { cmp.reverse() } else { cmp }
+                });
+            }
+        }
+
+        indices
+    }
+
+    /// Rows passing every range filter, ascending - None when there are none
+    /// Expects ensure_sorted_index() to have run for each filtered column.
+    fn range_rows(&self) -> Option<Vec<u32>> {
+        let mut result: Option<Vec<u32>> = None;
+        for filter in &self.view.range_filters {
+            let min = filter.min.map(IndexKey::number).unwrap_or(IndexKey::Null);
+            let max = filter.max.map(IndexKey::number);
+            let lower = match &min {
+                IndexKey::Null => Bound::Excluded(&min),  // Skip nulls
+                key => Bound::Included(key),
+            };
+            let upper = max.as_ref().map_or(Bound::Unbounded, Bound::Included);
+
+            let col = &self.columns[filter.column];
+            let rows = match col.secondary.as_ref().and_then(|index| index.range(lower, upper)) {
+                Some(mut rows) => {
+                    rows.sort_unstable();
+                    rows
+                }
+                // Hash-indexed column - no order to walk
+                None => self.scan_rows(col, |row| {
+                    let value = col.data.index_key(row);
+                    value != IndexKey::Null
+                        && filter.min.is_none_or(|min| value >= IndexKey::number(min))
+                        && filter.max.is_none_or(|max| value <= IndexKey::number(max))
+                }),
+            };
+
+            result = Some(match result {
+                None => rows,
+                Some(existing) => intersect_sorted(&existing, &rows),
+            });
+        }
+        result
+    }
+
+    /// Rows matching the text filter and the range filters, ascending
+    fn filtered_rows(&self, range_rows: Option<Vec<u32>>) -> Vec<u32> {
+        let has_text_filter = self.view.filter_regex.is_some() || !self.view.filter_terms.is_empty();
+        match range_rows {
+            Some(range) if !has_text_filter => range,
+            Some(range) => {
+                let text_rows = self.text_filtered_rows();
+                if range.len() < text_rows.len() {
+                    intersect_sorted(&range, &text_rows)
+                } else {
+                    intersect_sorted(&text_rows, &range)
+                }
+            }
+            None => self.text_filtered_rows(),
+        }
+    }
+
+    /// Non-deleted rows matching the current text filter, in ascending row order
+    fn text_filtered_rows(&self) -> Vec<u32> {
+        if let Some(filter) = &self.view.filter_regex {
+            return self.regex_rows(filter);
+        }
+
+        let terms = &self.view.filter_terms;
+        if terms.is_empty() {
+            // No filter - all non-deleted rows
+            return (0..self.row_count as u32)
+                .filter(|&i| !self.deleted[i as usize])
+                .collect();
+        }
+
+        // Intersect per-term candidates from the index, rarest first
+        let mut needs_verify = false;
+        let mut per_term: Vec<Vec<u32>> = terms
+            .iter()
+            .map(|term| {
+                if term.chars().count() < 3 {
+                    // Query too short for trigrams - unigram/bigram lists are exact
+                    self.trigram_index.search_short(term)
+                } else {
+                    needs_verify = true;
+                    self.trigram_index.search(term)
+                }
+            })
+            .collect();
+        per_term.sort_unstable_by_key(|rows| rows.len());
+
+        let mut candidates = per_term.swap_remove(0);
+        for rows in &per_term {
+            if candidates.is_empty() {
+                break;
+            }
+            candidates = intersect_sorted(&candidates, rows);
+        }
+
+        // Trigram hits are only candidates - verify they actually match
+        candidates
+            .into_iter()
+            .filter(|&i| {
+                !self.deleted[i as usize] && (!needs_verify || self.row_matches_filter(i as usize))
+            })
+            .collect()
+    }
+
+    /// Rows fuzzily matching every filter term, with their mean score
+    /// Candidates come from the bigram posting lists; each is then scored
+    /// against the whitespace-separated tokens of its indexed columns.
+    fn fuzzy_rows(&self) -> Vec<(u32, f64)> {
+        let threshold = self.view.fuzzy_threshold;
+        let terms: Vec<Vec<char>> = self.view.filter_terms
+            .iter()
+            .map(|term| term.chars().collect())
+            .collect();
+
+        let mut candidates: Option<Vec<u32>> = None;
+        for term in &terms {
+            let rows = self.trigram_index.fuzzy_candidates(term, threshold);
+            candidates = Some(match candidates {
+                None => rows,
+                Some(existing) => intersect_sorted(&existing, &rows),
+            });
+        }
+
+        let normalizer = &self.trigram_index.normalizer;
+        let mut scored = Vec::new();
+        'rows: for row in candidates.unwrap_or_default() {
+            if self.deleted[row as usize] {
+                continue;
+            }
+
+            let tokens: Vec<Vec<char>> = self.indexed_columns
+                .iter()
+                .filter_map(|&col_idx| self.columns[col_idx].data.get_string(row as usize))
+                .flat_map(|text| {
+                    normalizer.normalize(text)
+                        .split_whitespace()
+                        .map(|token| token.chars().collect())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            let mut total = 0.0;
+            for term in &terms {
+                let best = tokens.iter()
+                    .map(|token| similarity(term, token))
+                    .fold(0.0, f64::max);
+                if best < threshold {
+                    continue 'rows;
+                }
+                total += best;
+            }
+            scored.push((row, total / terms.len() as f64));
+        }
+
+        scored
+    }
+
+    /// Rows where some indexed column matches the regex
+    /// Required literals of the pattern prune candidates through the index.
+    fn regex_rows(&self, filter: &RegexFilter) -> Vec<u32> {
+        let normalizer = &self.trigram_index.normalizer;
+        let mut candidates: Option<Vec<u32>> = None;
+        for literal in filter.required_literals() {
+            let literal = normalizer.normalize(literal);
+            let rows = match literal.chars().count() {
+                0 => continue,
+                1 | 2 => self.trigram_index.search_short(&literal),
+                _ => self.trigram_index.search(&literal),
+            };
+            candidates = Some(match candidates {
+                None => rows,
+                Some(existing) => intersect_sorted(&existing, &rows),
+            });
+        }
+
+        let matches = |&row: &u32| {
+            !self.deleted[row as usize]
+                && self.indexed_columns.iter().any(|&col_idx| {
+                    self.columns[col_idx].data.get_string(row as usize)
+                        .is_some_and(|text| filter.is_match(text))
+                })
+        };
+        match candidates {
+            Some(rows) => rows.into_iter().filter(matches).collect(),
+            // Nothing to prune with - verify every row
+            None => (0..self.row_count as u32).filter(matches).collect(),
+        }
+    }
+
+    /// UTF-16 ranges of one cell that match the current filter (merged, sorted)
+    fn match_ranges(&self, row_idx: usize, col_idx: usize) -> Vec<(u32, u32)> {
+        let Some(text) = self.columns[col_idx].data.get_string(row_idx) else {
+            return vec![];
+        };
+
+        if let Some(filter) = &self.view.filter_regex {
+            // Regex runs on the original text - convert byte offsets to UTF-16
+            let utf16_at = |byte: usize| text[..byte].encode_utf16().count() as u32;
+            return filter.find_ranges(text)
+                .into_iter()
+                .filter(|(s, e)| s != e)
+                .map(|(s, e)| (utf16_at(s), utf16_at(e)))
+                .collect();
+        }
+
+        let terms: Vec<Vec<char>> = self.view.filter_terms
+            .iter()
+            .map(|term| term.chars().collect())
+            .collect();
+        if terms.is_empty() {
+            return vec![];
+        }
+
+        let (chars, offsets) = self.trigram_index.normalizer.normalize_with_offsets(text);
+        let span = |from: usize, to: usize| (offsets[from].0, offsets[to - 1].1);
+        let mut ranges = Vec::new();
+
+        if self.view.search_mode == SearchMode::Fuzzy {
+            // Highlight each token that fuzzily matches some term
+            let mut token_start = 0;
+            for token in chars.split(|c| c.is_whitespace()) {
+                let token_end = token_start + token.len();
+                for term in &terms {
+                    if similarity(term, token) < self.view.fuzzy_threshold {
+                        continue;
+                    }
+                    // Exact hits narrow down to the matching part
+                    match token.windows(term.len()).position(|w| w == term.as_slice()) {
+                        Some(pos) => ranges.push(span(token_start + pos, token_start + pos + term.len())),
+                        None => ranges.push(span(token_start, token_end)),
                     }
-                    ColumnData::Numbers(v) => {
-                        indices.sort_by(|&a, &b| {
-                            let va = v[a as usize];
-                            let vb = v[b as usize];
-                            let cmp = va.partial_cmp(&vb).unwrap_or(std::cmp::Ordering::Equal);
-                            if dir == SortDir::Desc { cmp.reverse() } else { cmp }
-                        });
+                }
+                token_start = token_end + 1;
+            }
+        } else {
+            for term in &terms {
+                for (pos, window) in chars.windows(term.len()).enumerate() {
+                    if window == term.as_slice() {
+                        ranges.push(span(pos, pos + term.len()));
                     }
                 }
             }
         }
 
-        self.view.cached_view = Some(indices);
+        merge_ranges(&mut ranges);
+        ranges
+    }
+
+    fn compile_filter_regex(pattern: &str) -> Result<Option<RegexFilter>, GridError> {
+        if pattern.is_empty() {
+            return Ok(None);
+        }
+        RegexFilter::compile(pattern).map(Some)
+    }
+
+    fn parse_filter_terms(&self, query: &str) -> Vec<String> {
+        let normalizer = &self.trigram_index.normalizer;
+        let mut terms: Vec<String> = tokenize_query(query)
+            .into_iter()
+            .map(|term| normalizer.normalize(term))
+            .filter(|term| !term.is_empty())
+            .collect();
+        terms.sort_unstable();
+        terms.dedup();
+        terms
     }
 
     fn row_matches_filter(&self, row_idx: usize) -> bool {
-        let filter = &self.view.filter_text;
-        if filter.is_empty() {
+        let terms = &self.view.filter_terms;
+        if terms.is_empty() {
             return true;
         }
 
-        // Check indexed columns
-        for &col_idx in &self.indexed_columns {
-            if let Some(text) = self.columns[col_idx].data.get_string(row_idx) {
-                if text.to_lowercase().contains(filter) {
-                    return true;
-                }
-            }
-        }
+        // Normalize indexed columns the same way as the trigram index
+        let normalizer = &self.trigram_index.normalizer;
+        let texts: Vec<String> = self.indexed_columns
+            .iter()
+            .filter_map(|&col_idx| self.columns[col_idx].data.get_string(row_idx))
+            .map(|text| normalizer.normalize(text))
+            .collect();
 
-        false
+        // Every term must appear in some indexed column
+        terms.iter().all(|term| texts.iter().any(|text| text.contains(term.as_str())))
     }
 }
 
@@ -750,7 +3035,7 @@ pub fn bench_store_load(count: u32) -> f64 {
     }
 
     let start = Date::now();
-    let mut store = GridStore::new(&schema.into()).unwrap();
+    let mut store = GridStore::new(&schema.into(), &JsValue::UNDEFINED).unwrap();
     store.load_rows(&rows.into()).unwrap();
     Date::now() - start
 }
@@ -781,12 +3066,12 @@ pub fn bench_store_filter(count: u32) -> f64 {
         rows.push(&row);
     }
 
-    let mut store = GridStore::new(&schema.into()).unwrap();
+    let mut store = GridStore::new(&schema.into(), &JsValue::UNDEFINED).unwrap();
     store.load_rows(&rows.into()).unwrap();
 
     // Benchmark filter
     let start = Date::now();
-    store.set_filter("SYM_42");
+    store.set_filter("SYM_42").unwrap();
     let _count = store.view_count();
     Date::now() - start
 }
@@ -823,7 +3108,7 @@ pub fn bench_store_update(count: u32, update_count: u32) -> f64 {
         rows.push(&row);
     }
 
-    let mut store = GridStore::new(&schema.into()).unwrap();
+    let mut store = GridStore::new(&schema.into(), &JsValue::UNDEFINED).unwrap();
     store.load_rows(&rows.into()).unwrap();
 
     // Create batch update
@@ -840,3 +3125,192 @@ pub fn bench_store_update(count: u32, update_count: u32) -> f64 {
     store.batch_update(&updates.into()).unwrap();
     Date::now() - start
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+        a.iter().copied().filter(|row| b.contains(row)).collect()
+    }
+
+    #[test]
+    fn gallop_finds_first_element_not_below_target() {
+        let list = [1, 3, 5, 7, 9, 11, 13];
+        assert_eq!(gallop(&list, 0), 0);
+        assert_eq!(gallop(&list, 1), 0);
+        assert_eq!(gallop(&list, 6), 3);
+        assert_eq!(gallop(&list, 13), 6);
+        assert_eq!(gallop(&list, 14), list.len());
+        assert_eq!(gallop(&[], 5), 0);
+    }
+
+    #[test]
+    fn intersect_sorted_merges_similar_sizes() {
+        let a = [1, 2, 4, 8, 16];
+        let b = [2, 3, 4, 5, 16, 17];
+        assert_eq!(intersect_sorted(&a, &b), vec![2, 4, 16]);
+        assert_eq!(intersect_sorted(&a, &[]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn intersect_sorted_gallops_lopsided_sizes() {
+        let large: Vec<u32> = (0..1000).map(|i| i * 3).collect();
+        let small = [0, 4, 299, 300, 2997, 2998, 5000];
+        assert!(small.len() * 16 < large.len());
+        assert_eq!(intersect_sorted(&small, &large), naive_intersect(&small, &large));
+        assert_eq!(intersect_sorted(&small, &large), vec![0, 300, 2997]);
+    }
+
+    #[test]
+    fn sorted_diff_splits_lost_and_gained() {
+        assert_eq!(sorted_diff(&[1, 3, 5, 7], &[3, 4, 7, 9]), (vec![1, 5], vec![4, 9]));
+        assert_eq!(sorted_diff(&[1, 2], &[1, 2]), (vec![], vec![]));
+        assert_eq!(sorted_diff(&[], &[2]), (vec![], vec![2]));
+    }
+
+    #[test]
+    fn posting_map_prunes_after_enough_empty_lists() {
+        // Gram `row` is unique to each row, gram SHARED is on every row
+        const SHARED: u32 = u32::MAX;
+        let mut map = PostingMap::new();
+        for row in 0..2000 {
+            map.add(row, &[row, SHARED]);
+        }
+
+        let threshold = PostingMap::<u32>::PRUNE_MIN_EMPTY as u32;
+        for row in 0..threshold {
+            map.remove(row, &[row, SHARED]);
+        }
+        assert_eq!(map.empty_lists, threshold as usize);
+        assert_eq!(map.lists.len(), 2001);
+
+        // One more empty list crosses the threshold
+        map.remove(threshold, &[threshold, SHARED]);
+        assert_eq!(map.empty_lists, 0);
+        // Live rows' unique grams plus SHARED
+        assert_eq!(map.lists.len(), 2000 - threshold as usize);
+
+        let live: Vec<u32> = (threshold + 1..2000).collect();
+        assert_eq!(map.intersect(&[SHARED]), live);
+        assert_eq!(map.intersect(&[1999, SHARED]), vec![1999]);
+        assert!(map.intersect(&[5]).is_empty());
+
+        // A pruned gram comes back like a new one
+        map.add(5, &[5]);
+        assert_eq!(map.intersect(&[5]), vec![5]);
+        assert_eq!(map.empty_lists, 0);
+    }
+
+    #[test]
+    fn bulk_add_in_chunks_matches_one_batch() {
+        let texts: Vec<(u32, String)> = (0..10).map(|row| (row, format!("item {}", row % 3))).collect();
+        let mut whole = TrigramIndex::new();
+        whole.add_bulk(&texts);
+        let mut chunked = TrigramIndex::new();
+        for chunk in texts.chunks(4) {
+            chunked.add_bulk(chunk);
+        }
+
+        for query in ["ite", "m 1", "m 2"] {
+            assert_eq!(chunked.search(query), whole.search(query), "{}", query);
+        }
+        assert_eq!(chunked.search("m 1"), vec![1, 4, 7]);
+        assert_eq!(chunked.trigrams.postings(), whole.trigrams.postings());
+    }
+
+    #[test]
+    fn trigram_update_moves_only_changed_grams() {
+        let mut index = TrigramIndex::new();
+        index.add(0, "apple");
+        index.add(1, "maple");
+        index.update(0, "apple", "apply");
+
+        assert_eq!(index.search("ppl"), vec![0]);
+        assert_eq!(index.search("ply"), vec![0]);
+        assert_eq!(index.search("ple"), vec![1]);
+        assert_eq!(index.search_short("e"), vec![1]);
+        assert_eq!(index.search_short("y"), vec![0]);
+    }
+
+    #[test]
+    fn top_rows_skip_string_nulls_with_or_without_an_index() {
+        // name column: "Apple", "Banana" (deleted), ""
+        let mut store = snapshot::read(&snapshot::tests::sample()).unwrap();
+        let name = store.column_idx("name").unwrap();
+        assert_eq!(store.top_rows(name, 10, SortDir::Asc), vec![0]);
+        assert_eq!(store.top_rows(name, 10, SortDir::Desc), vec![0]);
+
+        let entries = [0, 2].map(|row| (store.columns[name].data.index_key(row), row as u32));
+        store.columns[name].secondary = Some(SecondaryIndex::sorted_from(entries.to_vec()));
+        assert_eq!(store.top_rows(name, 10, SortDir::Asc), vec![0]);
+        assert_eq!(store.top_rows(name, 10, SortDir::Desc), vec![0]);
+
+        // price column: 1.5, NaN (deleted), -2.0 - indexed on first use
+        let price = store.column_idx("price").unwrap();
+        assert_eq!(store.top_rows(price, 1, SortDir::Desc), vec![0]);
+        assert_eq!(store.top_rows(price, 10, SortDir::Asc), vec![2, 0]);
+    }
+
+    #[test]
+    fn clearing_an_unknown_range_column_is_an_error() {
+        let mut store = snapshot::read(&snapshot::tests::sample()).unwrap();
+        store.set_range_filter("price", Some(0.0), None).unwrap();
+
+        let err = store.clear_range_filter(Some("pricee".to_string())).unwrap_err();
+        assert_eq!(err.code, ErrorCode::UnknownColumn);
+        assert_eq!(store.view.range_filters.len(), 1);
+
+        store.clear_range_filter(Some("price".to_string())).unwrap();
+        assert!(store.view.range_filters.is_empty());
+
+        store.set_range_filter("price", None, Some(1.0)).unwrap();
+        store.clear_range_filter(None).unwrap();
+        assert!(store.view.range_filters.is_empty());
+    }
+
+    #[test]
+    fn out_of_range_integer_keys_are_rejected_up_front() {
+        // Make price (1.5, NaN, -2.0) a strict integer key column
+        let mut store = snapshot::read(&snapshot::tests::sample()).unwrap();
+        let price = store.column_idx("price").unwrap();
+        store.key_columns = vec![price];
+        store.columns[price].col_type = ColumnType::Integer;
+        store.columns[price].coercion = CoercionPolicy::Strict;
+
+        assert_eq!(store.number_key_part(price, -2.0).unwrap(), RowKey::Int(-2));
+        let err = store.number_key_part(price, 3e9).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidKey);
+        assert!(err.message.contains("from -2147483648 to 2147483647"), "{}", err.message);
+
+        store.columns[price].coercion = CoercionPolicy::Lenient;
+        assert_eq!(store.number_key_part(price, 3e9).unwrap_err().code, ErrorCode::InvalidKey);
+        assert_eq!(store.number_key_part(price, 2.5).unwrap_err().code, ErrorCode::InvalidKey);
+
+        store.columns[price].coercion = CoercionPolicy::Legacy;
+        assert_eq!(store.number_key_part(price, 3e9).unwrap(), RowKey::Int(3_000_000_000));
+
+        // A bad stored key cell is an error, not a panic
+        assert_eq!(store.row_key(2).unwrap(), RowKey::Int(-2));
+        assert_eq!(store.row_key(1).unwrap_err().code, ErrorCode::MissingKey);
+        assert_eq!(store.row_key(0).unwrap_err().code, ErrorCode::InvalidKey);
+    }
+
+    #[test]
+    fn integer_columns_check_numbers_under_their_policy() {
+        let mut store = snapshot::read(&snapshot::tests::sample()).unwrap();
+        let price = store.column_idx("price").unwrap();
+        assert_eq!(store.columns[price].check_number(3e9), Ok(3e9));
+
+        let col = &mut store.columns[price];
+        col.col_type = ColumnType::Integer;
+        col.coercion = CoercionPolicy::Strict;
+        assert_eq!(col.check_number(7.0), Ok(7.0));
+        assert_eq!(col.check_number(3e9), Err(CellError::Rejected));
+        assert_eq!(col.check_number(1.5), Err(CellError::Rejected));
+        col.coercion = CoercionPolicy::Lenient;
+        assert_eq!(col.check_number(1.5), Err(CellError::Rounded(2.0)));
+        col.coercion = CoercionPolicy::Legacy;
+        assert_eq!(col.check_number(3e9), Ok(3e9));
+    }
+}
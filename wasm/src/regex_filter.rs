@@ -0,0 +1,174 @@
+//! Regular-expression filter accelerated by the trigram index
+//!
+//! The pattern is parsed once to find literal strings every match must
+//! contain (e.g. "ES" and "2" in `^ES[HMUZ]2[4-6]$`). Those literals prune
+//! candidates through the n-gram index; each survivor is then verified with
+//! the real regex engine against the original (unnormalized) column text.
+
+use regex::Regex;
+use regex_syntax::hir::{Hir, HirKind};
+
+use crate::error::{ErrorCode, GridError};
+
+pub(crate) struct RegexFilter {
+    regex: Regex,
+    // Substrings every match contains - used only to prune candidates
+    required: Vec<String>,
+}
+
+impl RegexFilter {
+    pub(crate) fn compile(pattern: &str) -> Result<Self, GridError> {
+        let hir = regex_syntax::Parser::new()
+            .parse(pattern)
+            .map_err(|e| syntax_error(pattern, &e))?;
+
+        let regex = Regex::new(pattern).map_err(|e| match e {
+            regex::Error::CompiledTooBig(limit) => GridError::new(
+                ErrorCode::RegexTooLarge,
+                format!("Regex exceeds the compiled size limit of {} bytes", limit),
+            ),
+            other => GridError::new(ErrorCode::InvalidRegex, other.to_string()),
+        })?;
+
+        let mut required = Vec::new();
+        collect_required(&hir, &mut required);
+        required.retain(|s| !s.is_empty());
+
+        Ok(Self { regex, required })
+    }
+
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Byte ranges of every match in `text`
+    pub(crate) fn find_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        self.regex.find_iter(text).map(|m| (m.start(), m.end())).collect()
+    }
+
+    pub(crate) fn required_literals(&self) -> &[String] {
+        &self.required
+    }
+}
+
+fn syntax_error(pattern: &str, err: &regex_syntax::Error) -> GridError {
+    let (message, span) = match err {
+        regex_syntax::Error::Parse(e) => (e.kind().to_string(), Some(e.span())),
+        regex_syntax::Error::Translate(e) => (e.kind().to_string(), Some(e.span())),
+        other => (other.to_string(), None),
+    };
+    let err = GridError::new(ErrorCode::InvalidRegex, format!("Invalid regex: {}", message));
+    match span {
+        Some(span) => {
+            let byte_offset = span.start.offset.min(pattern.len());
+            err.at(pattern[..byte_offset].chars().count())
+        }
+        None => err,
+    }
+}
+
+/// Collect literal strings that any match of `hir` must contain
+/// Conservative: alternations and optional parts contribute nothing.
+fn collect_required(hir: &Hir, out: &mut Vec<String>) {
+    match hir.kind() {
+        HirKind::Literal(lit) => out.push(String::from_utf8_lossy(&lit.0).into_owned()),
+        HirKind::Capture(cap) => collect_required(&cap.sub, out),
+        HirKind::Repetition(rep) if rep.min > 0 => collect_required(&rep.sub, out),
+        HirKind::Concat(parts) => {
+            // Adjacent literals form one longer run (more selective trigrams)
+            let mut run = String::new();
+            for part in parts {
+                if let HirKind::Literal(lit) = part.kind() {
+                    run.push_str(&String::from_utf8_lossy(&lit.0));
+                    continue;
+                }
+                if !run.is_empty() {
+                    out.push(std::mem::take(&mut run));
+                }
+                collect_required(part, out);
+            }
+            if !run.is_empty() {
+                out.push(run);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::Normalizer;
+
+    fn required(pattern: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        let hir = regex_syntax::Parser::new().parse(pattern).unwrap();
+        collect_required(&hir, &mut out);
+        out.retain(|s| !s.is_empty());
+        out
+    }
+
+    #[test]
+    fn joins_adjacent_literals_into_runs() {
+        assert_eq!(required("^ES[HMUZ]2[4-6]$"), vec!["ES", "2"]);
+        assert_eq!(required("foo(bar)+baz"), vec!["foo", "bar", "baz"]);
+        assert_eq!(required("a.b"), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn alternations_and_optional_parts_contribute_nothing() {
+        assert!(required("foo|bar").is_empty());
+        assert_eq!(required("x(foo|bar)y"), vec!["x", "y"]);
+        assert_eq!(required("ab(cd)?ef"), vec!["ab", "ef"]);
+        assert_eq!(required("ab(cd)*ef"), vec!["ab", "ef"]);
+        assert_eq!(required("ab(cd){0,3}ef"), vec!["ab", "ef"]);
+    }
+
+    #[test]
+    fn classes_contribute_nothing() {
+        assert!(required("[abc]+").is_empty());
+        assert_eq!(required(r"\d{3}-\w+"), vec!["-"]);
+    }
+
+    #[test]
+    fn case_insensitive_letters_become_classes() {
+        assert!(required("(?i)abc").is_empty());
+        assert_eq!(required("(?i)a1b2"), vec!["1", "2"]);
+        assert_eq!(required("(?i:ab)CD"), vec!["CD"]);
+    }
+
+    #[test]
+    fn prefilter_never_excludes_a_true_match() {
+        let patterns = [
+            "^ES[HMUZ]2[4-6]$",
+            "(?i)apple",
+            "(?i)Société",
+            "x(foo|bar)y",
+            "ab(cd)?ef",
+            "(abc){2}",
+            r"\bStraße\b",
+        ];
+        let texts = [
+            "ESH24", "ESZ26", "APPLE pie", "société générale", "SOCIÉTÉ", "xfooy", "xbary",
+            "abef", "abcdef", "abcabc", "Straße 5", "nothing here",
+        ];
+        // The index lookup folds diacritics too, so check under both normalizers
+        for normalizer in [Normalizer { fold_diacritics: false }, Normalizer { fold_diacritics: true }] {
+            for pattern in patterns {
+                let filter = RegexFilter::compile(pattern).unwrap();
+                for text in texts.iter().filter(|text| filter.is_match(text)) {
+                    let haystack = normalizer.normalize(text);
+                    for literal in filter.required_literals() {
+                        assert!(
+                            haystack.contains(&normalizer.normalize(literal)),
+                            "{:?} matches {:?} but lacks {:?}",
+                            pattern,
+                            text,
+                            literal
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Secondary indexes on non-primary-key columns
+//!
+//! Declared per column in the schema: `{ name: "account", type: "string", index: "hash" }`.
+//! - hash:   equality lookups in O(1) + O(matches)
+//! - sorted: equality and range lookups in O(log n + matches)
+//!
+//! Indexes only ever contain live rows - deleted rows are removed eagerly.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::ops::Bound;
+
+/// f64 with a total order (NaN never stored - nulls use IndexKey::Null)
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OrdF64(pub(crate) f64);
+
+impl OrdF64 {
+    fn normalized(self) -> f64 {
+        // -0.0 == 0.0 must hash and compare equal
+        if self.0 == 0.0 { 0.0 } else { self.0 }
+    }
+}
+
+impl PartialEq for OrdF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalized().total_cmp(&other.normalized())
+    }
+}
+
+impl Hash for OrdF64 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().to_bits().hash(state);
+    }
+}
+
+/// Value of one cell as an index key - nulls sort first
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub(crate) enum IndexKey {
+    Null,
+    Number(OrdF64),
+    Text(String),
+}
+
+impl IndexKey {
+    pub(crate) fn number(n: f64) -> Self {
+        if n.is_nan() { IndexKey::Null } else { IndexKey::Number(OrdF64(n)) }
+    }
+
+    /// Missing cell - NaN in number columns, "" in string columns
+    pub(crate) fn is_null(&self) -> bool {
+        match self {
+            IndexKey::Null => true,
+            IndexKey::Number(_) => false,
+            IndexKey::Text(s) => s.is_empty(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum IndexKind {
+    Hash,
+    Sorted,
+}
+
+impl IndexKind {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "hash" => Some(IndexKind::Hash),
+            "sorted" => Some(IndexKind::Sorted),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            IndexKind::Hash => "hash",
+            IndexKind::Sorted => "sorted",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum SecondaryIndex {
+    // key -> rows (sorted ascending)
+    Hash(HashMap<IndexKey, Vec<u32>>),
+    // (key, row) pairs in key order, rows ascending within a key
+    Sorted(BTreeSet<(IndexKey, u32)>),
+}
+
+impl SecondaryIndex {
+    pub(crate) fn new(kind: IndexKind) -> Self {
+        match kind {
+            IndexKind::Hash => SecondaryIndex::Hash(HashMap::new()),
+            IndexKind::Sorted => SecondaryIndex::Sorted(BTreeSet::new()),
+        }
+    }
+
+    /// Bulk-build a sorted index - O(n log n), cheaper than n inserts
+    pub(crate) fn sorted_from(mut entries: Vec<(IndexKey, u32)>) -> Self {
+        entries.sort_unstable();
+        SecondaryIndex::Sorted(entries.into_iter().collect())
+    }
+
+    pub(crate) fn insert(&mut self, key: IndexKey, row: u32) {
+        match self {
+            SecondaryIndex::Hash(map) => {
+                let rows = map.entry(key).or_default();
+                match rows.last() {
+                    Some(&last) if last >= row => {
+                        if let Err(pos) = rows.binary_search(&row) {
+                            rows.insert(pos, row);
+                        }
+                    }
+                    _ => rows.push(row),
+                }
+            }
+            SecondaryIndex::Sorted(set) => {
+                set.insert((key, row));
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &IndexKey, row: u32) {
+        match self {
+            SecondaryIndex::Hash(map) => {
+                if let Some(rows) = map.get_mut(key) {
+                    if let Ok(pos) = rows.binary_search(&row) {
+                        rows.remove(pos);
+                    }
+                    if rows.is_empty() {
+                        map.remove(key);
+                    }
+                }
+            }
+            SecondaryIndex::Sorted(set) => {
+                set.remove(&(key.clone(), row));
+            }
+        }
+    }
+
+    /// Rows whose value equals `key`, ascending
+    pub(crate) fn get(&self, key: &IndexKey) -> Vec<u32> {
+        match self {
+            SecondaryIndex::Hash(map) => map.get(key).cloned().unwrap_or_default(),
+            SecondaryIndex::Sorted(_) => {
+                self.range(Bound::Included(key), Bound::Included(key)).unwrap_or_default()
+            }
+        }
+    }
+
+    /// Rows whose value lies within the bounds, in key order
+    /// Returns None for hash indexes, which can't serve ranges.
+    pub(crate) fn range(&self, min: Bound<&IndexKey>, max: Bound<&IndexKey>) -> Option<Vec<u32>> {
+        let SecondaryIndex::Sorted(set) = self else {
+            return None;
+        };
+
+        // (key, row) bounds: rows span 0..=u32::MAX within one key
+        let lower = match min {
+            Bound::Included(k) => Bound::Included((k.clone(), 0)),
+            Bound::Excluded(k) => Bound::Excluded((k.clone(), u32::MAX)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let upper = match max {
+            Bound::Included(k) => Bound::Included((k.clone(), u32::MAX)),
+            Bound::Excluded(k) => Bound::Excluded((k.clone(), 0)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        if let (Bound::Included(lo) | Bound::Excluded(lo), Bound::Included(hi) | Bound::Excluded(hi)) =
+            (&lower, &upper)
+        {
+            if lo > hi {
+                return Some(vec![]);
+            }
+        }
+
+        Some(set.range((lower, upper)).map(|&(_, row)| row).collect())
+    }
+
+    /// Every row in key order - O(n), replaces a sort on this column
+    /// Ties keep ascending row order in both directions, like a stable sort.
+    pub(crate) fn ordered_rows(&self, descending: bool) -> Option<Vec<u32>> {
+        let SecondaryIndex::Sorted(set) = self else {
+            return None;
+        };
+        if !descending {
+            return Some(set.iter().map(|&(_, row)| row).collect());
+        }
+
+        let mut rows = Vec::with_capacity(set.len());
+        let mut run_start = 0;
+        let mut run_key: Option<&IndexKey> = None;
+        for (key, row) in set.iter().rev() {
+            if run_key != Some(key) {
+                rows[run_start..].reverse();
+                run_start = rows.len();
+                run_key = Some(key);
+            }
+            rows.push(*row);
+        }
+        rows[run_start..].reverse();
+        Some(rows)
+    }
+
+    /// First `n` non-null rows in key order - O(log n + n)
+    /// String nulls ("") sort first among texts, so skipping them costs one
+    /// pass over that run in ascending order.
+    pub(crate) fn top_rows(&self, n: usize, descending: bool) -> Option<Vec<u32>> {
+        let SecondaryIndex::Sorted(set) = self else {
+            return None;
+        };
+        let non_null = set
+            .range((Bound::Excluded((IndexKey::Null, u32::MAX)), Bound::Unbounded))
+            .filter(|(key, _)| !key.is_null());
+        let rows = if descending {
+            non_null.rev().take(n).map(|&(_, row)| row).collect()
+        } else {
+            non_null.take(n).map(|&(_, row)| row).collect()
+        };
+        Some(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(n: f64) -> IndexKey {
+        IndexKey::number(n)
+    }
+
+    /// Sorted index over rows 0.. with the given values
+    fn sorted(values: &[IndexKey]) -> SecondaryIndex {
+        let entries = values.iter().cloned().zip(0..).collect();
+        SecondaryIndex::sorted_from(entries)
+    }
+
+    #[test]
+    fn ordered_rows_keep_ties_ascending_both_ways() {
+        let index = sorted(&[num(2.0), num(1.0), num(2.0), IndexKey::Null, num(1.0), num(3.0)]);
+        assert_eq!(index.ordered_rows(false), Some(vec![3, 1, 4, 0, 2, 5]));
+        assert_eq!(index.ordered_rows(true), Some(vec![5, 0, 2, 1, 4, 3]));
+        assert_eq!(SecondaryIndex::new(IndexKind::Hash).ordered_rows(false), None);
+    }
+
+    #[test]
+    fn top_rows_skip_nulls() {
+        let index = sorted(&[num(2.0), IndexKey::Null, num(-1.0), num(5.0), IndexKey::Null]);
+        assert_eq!(index.top_rows(2, false), Some(vec![2, 0]));
+        assert_eq!(index.top_rows(2, true), Some(vec![3, 0]));
+        assert_eq!(index.top_rows(10, false), Some(vec![2, 0, 3]));
+
+        let text = |s: &str| IndexKey::Text(s.to_string());
+        let index = sorted(&[text("b"), text(""), text("a"), text("")]);
+        assert_eq!(index.top_rows(10, false), Some(vec![2, 0]));
+        assert_eq!(index.top_rows(10, true), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn range_honours_bound_kinds() {
+        let index = sorted(&[num(1.0), num(2.0), num(2.0), num(3.0), num(4.0)]);
+        let (two, three) = (num(2.0), num(3.0));
+        let range = |min, max| index.range(min, max).unwrap();
+
+        assert_eq!(range(Bound::Included(&two), Bound::Included(&three)), vec![1, 2, 3]);
+        assert_eq!(range(Bound::Excluded(&two), Bound::Included(&three)), vec![3]);
+        assert_eq!(range(Bound::Included(&two), Bound::Excluded(&three)), vec![1, 2]);
+        assert_eq!(range(Bound::Excluded(&two), Bound::Excluded(&three)), Vec::<u32>::new());
+        assert_eq!(range(Bound::Unbounded, Bound::Excluded(&two)), vec![0]);
+        assert_eq!(range(Bound::Included(&three), Bound::Included(&two)), Vec::<u32>::new());
+        assert_eq!(SecondaryIndex::new(IndexKind::Hash).range(Bound::Unbounded, Bound::Unbounded), None);
+    }
+
+    #[test]
+    fn range_excluding_null_skips_missing_cells() {
+        let index = sorted(&[IndexKey::Null, num(-5.0), IndexKey::Null, num(0.0)]);
+        let rows = index.range(Bound::Excluded(&IndexKey::Null), Bound::Unbounded);
+        assert_eq!(rows, Some(vec![1, 3]));
+        let rows = index.range(Bound::Excluded(&IndexKey::Null), Bound::Included(&num(-1.0)));
+        assert_eq!(rows, Some(vec![1]));
+        // Unbounded below does include nulls - callers exclude them explicitly
+        assert_eq!(index.range(Bound::Unbounded, Bound::Included(&num(-1.0))), Some(vec![0, 2, 1]));
+    }
+
+    #[test]
+    fn get_works_on_both_kinds() {
+        let values = [num(1.0), num(-0.0), num(1.0), num(0.0)];
+        let mut hash = SecondaryIndex::new(IndexKind::Hash);
+        for (row, key) in values.iter().enumerate() {
+            hash.insert(key.clone(), row as u32);
+        }
+        for index in [hash, sorted(&values)] {
+            assert_eq!(index.get(&num(1.0)), vec![0, 2]);
+            assert_eq!(index.get(&num(0.0)), vec![1, 3]);
+            assert!(index.get(&num(7.0)).is_empty());
+        }
+    }
+}
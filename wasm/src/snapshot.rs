@@ -0,0 +1,485 @@
+//! Binary snapshots of a whole GridStore for `serialize` / `deserialize`
+//!
+//! Restoring copies column data straight into place instead of walking JS
+//! objects, and can skip the trigram rebuild if the index was saved too.
+//!
+//! Layout (all integers/floats little-endian):
+//!
+//! ```text
+//! snapshot := "AGSN", u16 version, u8 flags (bit 0 = trigram index included)
+//!             str default_coercion, u8 fold_diacritics
+//!             u32 column_count, column * column_count
+//!             u32 key_count, u32 key_column * key_count
+//!             u32 row_count, deleted bitmap (ceil(row_count / 8) bytes)
+//!             cells * column_count, [index]
+//! column   := str name, str type, u8 indexed, opt_str index, str coercion,
+//!             u8 nullable, str collation, opt_str format, opt_str units,
+//!             opt_str meta (JSON)
+//! cells    := number -> f64 * row_count
+//!             string -> (u32 byte_length, UTF-8 bytes) * row_count
+//! index    := postings(3) trigrams, postings(2) short grams
+//! postings := u32 list_count, (u32 char * n, u32 len, u32 row * len) * list_count
+//! str      := u32 byte_length, UTF-8 bytes
+//! opt_str  := u8 present, [str]
+//! ```
+//!
+//! The primary key map and declared secondary indexes are rebuilt on restore.
+//! Filter/sort state and the coercion log are not saved.
+
+use crate::coerce::{CoercionLog, CoercionPolicy};
+use crate::error::{ErrorCode, GridError};
+use crate::key::RowKey;
+use crate::secondary::{IndexKind, SecondaryIndex};
+use crate::text::Normalizer;
+use crate::{Collation, Column, ColumnData, ColumnType, GridStore, PostingMap, TrigramIndex, ViewState};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"AGSN";
+/// Bump on any layout change - older snapshots are rejected, not migrated
+const VERSION: u16 = 1;
+const FLAG_TEXT_INDEX: u8 = 1;
+
+// ============================================================================
+// Writer
+// ============================================================================
+
+pub(crate) fn write(store: &GridStore, include_index: bool) -> Vec<u8> {
+    let mut out = Writer(Vec::new());
+    out.0.extend_from_slice(MAGIC);
+    out.0.extend_from_slice(&VERSION.to_le_bytes());
+    out.u8(if include_index { FLAG_TEXT_INDEX } else { 0 });
+    out.str(store.default_coercion.as_str());
+    out.u8(store.trigram_index.normalizer.fold_diacritics as u8);
+
+    out.u32(store.columns.len() as u32);
+    for col in &store.columns {
+        out.str(&col.name);
+        out.str(col.col_type.as_str());
+        out.u8(col.indexed as u8);
+        out.opt_str(col.index.map(|kind| kind.as_str()));
+        out.str(col.coercion.as_str());
+        out.u8(col.nullable as u8);
+        out.str(col.collation.as_str());
+        out.opt_str(col.format.as_deref());
+        out.opt_str(col.units.as_deref());
+        let meta = col.meta.as_ref()
+            .and_then(|meta| js_sys::JSON::stringify(meta).ok())
+            .and_then(|json| json.as_string());
+        out.opt_str(meta.as_deref());
+    }
+
+    out.u32(store.key_columns.len() as u32);
+    for &col_idx in &store.key_columns {
+        out.u32(col_idx as u32);
+    }
+
+    out.u32(store.row_count as u32);
+    let mut bitmap = vec![0u8; store.row_count.div_ceil(8)];
+    for (row, _) in store.deleted.iter().enumerate().filter(|(_, &deleted)| deleted) {
+        bitmap[row / 8] |= 1 << (row % 8);
+    }
+    out.0.extend_from_slice(&bitmap);
+
+    for col in &store.columns {
+        match &col.data {
+            ColumnData::Numbers(v) => {
+                out.0.reserve(v.len() * 8);
+                for n in v {
+                    out.0.extend_from_slice(&n.to_le_bytes());
+                }
+            }
+            ColumnData::Strings(v) => {
+                for s in v {
+                    out.str(s);
+                }
+            }
+        }
+    }
+
+    if include_index {
+        out.postings(&store.trigram_index.trigrams);
+        out.postings(&store.trigram_index.short_grams);
+    }
+    out.0
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(s) => {
+                self.u8(1);
+                self.str(s);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    /// Non-empty lists only, in gram order so equal stores give equal bytes
+    fn postings<const N: usize>(&mut self, map: &PostingMap<[char; N]>) {
+        let mut lists: Vec<_> = map.lists.iter().filter(|(_, rows)| !rows.is_empty()).collect();
+        lists.sort_unstable_by_key(|&(gram, _)| gram);
+
+        self.u32(lists.len() as u32);
+        for (gram, rows) in lists {
+            for &c in gram {
+                self.u32(c as u32);
+            }
+            self.u32(rows.len() as u32);
+            for &row in rows {
+                self.u32(row);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Reader
+// ============================================================================
+
+pub(crate) fn read(bytes: &[u8]) -> Result<GridStore, GridError> {
+    let mut r = Reader { bytes, pos: 0 };
+    if r.take(4)? != MAGIC {
+        return Err(GridError::new(ErrorCode::InvalidSnapshot, "Not a grid snapshot").at(0));
+    }
+    let version = u16::from_le_bytes(r.take(2)?.try_into().unwrap());
+    if version != VERSION {
+        return Err(GridError::new(
+            ErrorCode::SnapshotVersion,
+            format!("Snapshot format version {} is not supported (expected {})", version, VERSION),
+        ));
+    }
+    let flags = r.u8()?;
+    let default_coercion = r.parse(CoercionPolicy::parse, "coercion policy")?;
+    let normalizer = Normalizer { fold_diacritics: r.u8()? != 0 };
+
+    let column_count = r.u32()? as usize;
+    let mut columns = Vec::new();
+    let mut column_index = HashMap::new();
+    for pos in 0..column_count {
+        let column = r.column()?;
+        if column_index.insert(column.name.clone(), pos).is_some() {
+            return Err(r.error(format!("duplicate column {}", column.name)));
+        }
+        columns.push(column);
+    }
+
+    let key_count = r.u32()? as usize;
+    let mut key_columns = Vec::new();
+    for _ in 0..key_count {
+        let col_idx = r.u32()? as usize;
+        if col_idx >= column_count || key_columns.contains(&col_idx) {
+            return Err(r.error(format!("bad primary key column {}", col_idx)));
+        }
+        key_columns.push(col_idx);
+    }
+    if key_columns.is_empty() {
+        return Err(r.error("no primary key".to_string()));
+    }
+
+    let row_count = r.u32()? as usize;
+    let bitmap = r.take(row_count.div_ceil(8))?;
+    let deleted: Vec<bool> = (0..row_count).map(|row| bitmap[row / 8] & (1 << (row % 8)) != 0).collect();
+
+    for col in &mut columns {
+        match &mut col.data {
+            ColumnData::Numbers(v) => {
+                let raw = r.take(row_count.checked_mul(8).ok_or_else(|| r.error("row count overflow".to_string()))?)?;
+                v.extend(raw.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())));
+            }
+            ColumnData::Strings(v) => {
+                // Each string takes at least its 4-byte length - don't trust row_count further
+                v.reserve(row_count.min(r.remaining() / 4));
+                for _ in 0..row_count {
+                    v.push(r.str()?.to_string());
+                }
+            }
+        }
+    }
+
+    let mut trigram_index = TrigramIndex::new();
+    trigram_index.normalizer = normalizer;
+    let has_index = flags & FLAG_TEXT_INDEX != 0;
+    if has_index {
+        r.postings(&mut trigram_index.trigrams, row_count)?;
+        r.postings(&mut trigram_index.short_grams, row_count)?;
+    }
+    if r.pos != bytes.len() {
+        return Err(r.error("trailing bytes after snapshot".to_string()));
+    }
+
+    let indexed_columns = (0..column_count).filter(|&c| columns[c].indexed).collect();
+    let mut store = GridStore {
+        columns,
+        column_index,
+        row_count,
+        key_columns,
+        id_to_row: HashMap::with_capacity(row_count),
+        deleted,
+        trigram_index,
+        indexed_columns,
+        default_coercion,
+        coercion_errors: CoercionLog::default(),
+        view: ViewState::new(),
+    };
+
+    // Deleted rows keep their keys, as in the live store
+    for row in 0..row_count {
+        let parts = store.key_columns
+            .iter()
+            .map(|&col_idx| store.key_part(col_idx, &store.columns[col_idx].data, row))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| GridError::new(ErrorCode::InvalidSnapshot, format!("Invalid snapshot: row {}: {}", row, e.message)))?;
+        let key = RowKey::from_parts(parts);
+        if store.id_to_row.insert(key.clone(), row as u32).is_some() {
+            return Err(GridError::new(ErrorCode::InvalidSnapshot, format!("Invalid snapshot: duplicate ID {}", key)));
+        }
+    }
+
+    for col in &mut store.columns {
+        let Some(kind) = col.index else { continue };
+        let live = (0..row_count as u32).filter(|&row| !store.deleted[row as usize]);
+        col.secondary = Some(match kind {
+            IndexKind::Sorted => SecondaryIndex::sorted_from(live.map(|row| (col.data.index_key(row as usize), row)).collect()),
+            IndexKind::Hash => {
+                let mut index = SecondaryIndex::new(kind);
+                for row in live {
+                    index.insert(col.data.index_key(row as usize), row);
+                }
+                index
+            }
+        });
+    }
+
+    if !has_index {
+        store.rebuild_text_index();
+    }
+    Ok(store)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn column(&mut self) -> Result<Column, GridError> {
+        let name = self.str()?.to_string();
+        let col_type = self.parse(ColumnType::parse, "column type")?;
+        let indexed = self.u8()? != 0;
+        let index = match self.opt_str()? {
+            Some(kind) => Some(IndexKind::parse(kind).ok_or_else(|| self.error(format!("unknown index type {}", kind)))?),
+            None => None,
+        };
+        let coercion = self.parse(CoercionPolicy::parse, "coercion policy")?;
+        let nullable = self.u8()? != 0;
+        let collation = self.parse(Collation::parse, "collation")?;
+        let format = self.opt_str()?.map(str::to_string);
+        let units = self.opt_str()?.map(str::to_string);
+        let meta = match self.opt_str()? {
+            Some(json) => Some(js_sys::JSON::parse(json).map_err(|_| self.error(format!("bad meta on column {}", name)))?),
+            None => None,
+        };
+
+        let data = match col_type {
+            ColumnType::String => ColumnData::Strings(Vec::new()),
+            ColumnType::Number | ColumnType::Integer => ColumnData::Numbers(Vec::new()),
+        };
+        Ok(Column {
+            name,
+            col_type,
+            data,
+            indexed,
+            index,
+            secondary: None,
+            coercion,
+            nullable,
+            collation,
+            format,
+            units,
+            meta,
+        })
+    }
+
+    fn postings<const N: usize>(&mut self, map: &mut PostingMap<[char; N]>, row_count: usize) -> Result<(), GridError> {
+        let count = self.u32()? as usize;
+        // Each list takes at least its gram chars and length
+        map.lists.reserve(count.min(self.remaining() / ((N + 1) * 4)));
+        for _ in 0..count {
+            let mut gram = ['\0'; N];
+            for c in &mut gram {
+                let code = self.u32()?;
+                *c = char::from_u32(code).ok_or_else(|| self.error(format!("bad char {:#x} in text index", code)))?;
+            }
+            let len = self.u32()? as usize;
+            let raw = self.take(len.checked_mul(4).ok_or_else(|| self.error("posting list overflow".to_string()))?)?;
+            let rows: Vec<u32> = raw.chunks_exact(4).map(|b| u32::from_le_bytes(b.try_into().unwrap())).collect();
+            // Searches rely on sorted, unique, in-range postings
+            if rows.windows(2).any(|w| w[0] >= w[1]) || rows.last().is_some_and(|&row| row as usize >= row_count) {
+                return Err(self.error("text index posting list out of order or range".to_string()));
+            }
+            map.lists.insert(gram, rows);
+        }
+        Ok(())
+    }
+
+    fn parse<T>(&mut self, parse: fn(&str) -> Option<T>, what: &str) -> Result<T, GridError> {
+        let start = self.pos;
+        let text = self.str()?;
+        parse(text).ok_or_else(|| {
+            GridError::new(ErrorCode::InvalidSnapshot, format!("Invalid snapshot: unknown {} {}", what, text)).at(start)
+        })
+    }
+
+    fn u8(&mut self) -> Result<u8, GridError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, GridError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn str(&mut self) -> Result<&'a str, GridError> {
+        let len = self.u32()? as usize;
+        let start = self.pos;
+        std::str::from_utf8(self.take(len)?)
+            .map_err(|_| GridError::new(ErrorCode::InvalidSnapshot, "Invalid snapshot: string is not valid UTF-8").at(start))
+    }
+
+    fn opt_str(&mut self) -> Result<Option<&'a str>, GridError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => self.str().map(Some),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], GridError> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| self.error("unexpected end of input".to_string()))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn error(&self, message: String) -> GridError {
+        GridError::new(ErrorCode::InvalidSnapshot, format!("Invalid snapshot at byte {}: {}", self.pos, message)).at(self.pos)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Snapshot of a store with columns id (string key), price (number) and
+    /// name (indexed string); row 1 is deleted
+    pub(crate) fn sample() -> Vec<u8> {
+        let mut out = Writer(Vec::new());
+        out.0.extend_from_slice(MAGIC);
+        out.0.extend_from_slice(&VERSION.to_le_bytes());
+        out.u8(0);
+        out.str("legacy");
+        out.u8(0);
+
+        out.u32(3);
+        for (name, col_type, indexed) in [("id", "string", 0), ("price", "number", 0), ("name", "string", 1)] {
+            out.str(name);
+            out.str(col_type);
+            out.u8(indexed);
+            out.opt_str(None);
+            out.str("legacy");
+            out.u8(1);
+            out.str("binary");
+            out.opt_str(None);
+            out.opt_str(None);
+            out.opt_str(None);
+        }
+        out.u32(1);
+        out.u32(0);
+
+        out.u32(3);
+        out.u8(0b010);
+        for id in ["a", "b", "c"] {
+            out.str(id);
+        }
+        for price in [1.5, f64::NAN, -2.0] {
+            out.0.extend_from_slice(&price.to_le_bytes());
+        }
+        for name in ["Apple", "Banana", ""] {
+            out.str(name);
+        }
+        out.0
+    }
+
+    #[test]
+    fn round_trips_without_index() {
+        let bytes = sample();
+        let store = read(&bytes).unwrap();
+        assert_eq!(store.row_count, 3);
+        assert_eq!(store.deleted, vec![false, true, false]);
+        assert_eq!(store.id_to_row.get(&RowKey::Str("c".to_string())), Some(&2));
+        assert_eq!(write(&store, false), bytes);
+    }
+
+    #[test]
+    fn round_trips_with_index() {
+        let store = read(&sample()).unwrap();
+        let with_index = write(&store, true);
+        let restored = read(&with_index).unwrap();
+        assert_eq!(restored.trigram_index.search("ppl"), vec![0]);
+        // Deleted rows aren't indexed
+        assert!(restored.trigram_index.search("ana").is_empty());
+        assert_eq!(write(&restored, true), with_index);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = sample();
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        let err = read(&bytes).err().unwrap();
+        assert_eq!(err.code, ErrorCode::SnapshotVersion);
+
+        bytes[..4].copy_from_slice(b"NOPE");
+        assert_eq!(read(&bytes).err().unwrap().code, ErrorCode::InvalidSnapshot);
+    }
+
+    #[test]
+    fn rejects_truncated_and_trailing_bytes() {
+        let bytes = sample();
+        for len in 0..bytes.len() {
+            assert!(read(&bytes[..len]).is_err(), "accepted {} of {} bytes", len, bytes.len());
+        }
+        let mut padded = bytes;
+        padded.push(0);
+        assert_eq!(read(&padded).err().unwrap().code, ErrorCode::InvalidSnapshot);
+    }
+
+    #[test]
+    fn rejects_row_count_beyond_input() {
+        // Header claims 80,000 rows; the bitmap fits but the cells don't
+        let mut bytes = sample();
+        let cells_len = 3 * (4 + 1) + 3 * 8 + (4 + 5) + (4 + 6) + 4;
+        let row_count_at = bytes.len() - cells_len - 1 - 4;
+        assert_eq!(bytes[row_count_at..row_count_at + 4], 3u32.to_le_bytes());
+        bytes[row_count_at..row_count_at + 4].copy_from_slice(&80_000u32.to_le_bytes());
+        bytes.extend(std::iter::repeat_n(0, 10_000));
+        assert_eq!(read(&bytes).err().unwrap().code, ErrorCode::InvalidSnapshot);
+    }
+}
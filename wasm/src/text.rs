@@ -0,0 +1,329 @@
+//! Search text normalization
+//!
+//! The trigram index, the filter check and match highlighting must all agree
+//! on what "the same text" means, so they share this one normalizer:
+//!
+//! 1. Canonical decomposition per char, so precomposed "é" and "e" + U+0301
+//!    produce the same sequence
+//! 2. Full Unicode case folding ("Straße" -> "strasse", "ΣΑΣ" -> "σασ")
+//! 3. Optionally, diacritic folding - combining marks are dropped, so
+//!    "societe" matches "Société"
+//!
+//! Every output char comes from exactly one input char, which keeps offsets
+//! traceable back to the original text.
+
+use caseless::Caseless;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) struct Normalizer {
+    pub(crate) fold_diacritics: bool,
+}
+
+impl Normalizer {
+    /// Normalize a whole string
+    pub(crate) fn normalize(&self, text: &str) -> String {
+        if text.is_ascii() {
+            return text.to_ascii_lowercase();
+        }
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            self.fold_char(c, |folded| out.push(folded));
+        }
+        out
+    }
+
+    /// Normalize into chars, ready for n-gram windows
+    pub(crate) fn normalize_chars(&self, text: &str) -> Vec<char> {
+        if text.is_ascii() {
+            return text.bytes().map(|b| b.to_ascii_lowercase() as char).collect();
+        }
+        let mut out = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            self.fold_char(c, |folded| out.push(folded));
+        }
+        out
+    }
+
+    /// Normalize into chars, keeping for each output char the UTF-16 range
+    /// [start, end) of the source char - used to map matches back for highlighting
+    pub(crate) fn normalize_with_offsets(&self, text: &str) -> (Vec<char>, Vec<(u32, u32)>) {
+        let mut chars = Vec::with_capacity(text.len());
+        let mut offsets = Vec::with_capacity(text.len());
+        let mut pos = 0u32;
+        for c in text.chars() {
+            let end = pos + c.len_utf16() as u32;
+            self.fold_char(c, |folded| {
+                chars.push(folded);
+                offsets.push((pos, end));
+            });
+            pos = end;
+        }
+        (chars, offsets)
+    }
+
+    /// Emit the normalized form of one char (zero or more chars)
+    pub(crate) fn fold_char(&self, c: char, mut emit: impl FnMut(char)) {
+        if c.is_ascii() {
+            emit(c.to_ascii_lowercase());
+            return;
+        }
+        decompose_canonical(c, |d| {
+            if self.fold_diacritics && is_combining_mark(d) {
+                return;
+            }
+            for folded in std::iter::once(d).default_case_fold() {
+                if self.fold_diacritics {
+                    emit(fold_stroke(folded));
+                } else {
+                    emit(folded);
+                }
+            }
+        });
+    }
+}
+
+/// Latin letters whose diacritic is not a combining mark (no decomposition)
+fn fold_stroke(c: char) -> char {
+    match c {
+        'ø' => 'o',
+        'ł' => 'l',
+        'đ' => 'd',
+        'ħ' => 'h',
+        'ŧ' => 't',
+        'ƀ' => 'b',
+        'ı' => 'i',
+        _ => c,
+    }
+}
+
+/// Merge overlapping/adjacent [start, end) ranges in place
+pub(crate) fn merge_ranges(ranges: &mut Vec<(u32, u32)>) {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for &(start, end) in ranges.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *ranges = merged;
+}
+
+/// Split a filter query into search terms
+/// Whitespace separates terms; "double quoted" phrases are kept whole so
+/// they match as exact substrings. An unclosed quote runs to the end.
+pub(crate) fn tokenize_query(query: &str) -> Vec<&str> {
+    let mut terms = Vec::new();
+    let mut rest = query;
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        let (term, remainder) = if let Some(quoted) = rest.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            }
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+
+        if !term.is_empty() {
+            terms.push(term);
+        }
+        rest = remainder;
+    }
+
+    terms
+}
+
+/// Fuzzy similarity of a search term to one token, in [0, 1]
+/// Blends bigram overlap (Dice) with edit-distance similarity, so both
+/// "APPL" ~ "AAPL" (one substitution) and transpositions score well.
+/// A token containing the term outright scores 1.
+pub(crate) fn similarity(term: &[char], token: &[char]) -> f64 {
+    if term.is_empty() || token.is_empty() {
+        return 0.0;
+    }
+    if token.windows(term.len()).any(|w| w == term) {
+        return 1.0;
+    }
+
+    let dice = bigram_dice(term, token);
+    // Long tokens can't be close in edit distance - skip the O(m*n) pass
+    if token.len() > term.len() * 2 + 8 {
+        return 0.5 * dice;
+    }
+    let max_len = term.len().max(token.len());
+    let edit = 1.0 - edit_distance(term, token) as f64 / max_len as f64;
+    0.5 * dice + 0.5 * edit
+}
+
+/// Dice coefficient over unique bigrams (unigrams for 1-char strings)
+fn bigram_dice(a: &[char], b: &[char]) -> f64 {
+    fn grams(s: &[char]) -> Vec<[char; 2]> {
+        let mut grams: Vec<[char; 2]> = if s.len() == 1 {
+            vec![[s[0], '\0']]
+        } else {
+            s.windows(2).map(|w| [w[0], w[1]]).collect()
+        };
+        grams.sort_unstable();
+        grams.dedup();
+        grams
+    }
+
+    let (ga, gb) = (grams(a), grams(b));
+    let common = ga.iter().filter(|g| gb.binary_search(g).is_ok()).count();
+    2.0 * common as f64 / (ga.len() + gb.len()) as f64
+}
+
+/// Optimal string alignment distance (Levenshtein + adjacent transpositions)
+pub(crate) fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let n = b.len();
+    let mut prev2 = vec![0; n + 1];
+    let mut prev: Vec<usize> = (0..=n).collect();
+    let mut cur = vec![0; n + 1];
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=n {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: Normalizer = Normalizer { fold_diacritics: false };
+    const FOLDING: Normalizer = Normalizer { fold_diacritics: true };
+
+    #[test]
+    fn composed_and_decomposed_forms_normalize_alike() {
+        assert_eq!(PLAIN.normalize("caf\u{e9}"), PLAIN.normalize("cafe\u{301}"));
+        assert_eq!(PLAIN.normalize("caf\u{e9}"), "cafe\u{301}");
+    }
+
+    #[test]
+    fn applies_full_case_folding() {
+        assert_eq!(PLAIN.normalize("Straße"), "strasse");
+        assert_eq!(PLAIN.normalize("ΣΑΣ"), "σασ");
+        assert_eq!(PLAIN.normalize("MiXeD"), "mixed");
+    }
+
+    #[test]
+    fn diacritic_folding_is_optional() {
+        assert_eq!(FOLDING.normalize("Société"), "societe");
+        assert_eq!(FOLDING.normalize("Łódź"), "lodz");
+        assert_eq!(FOLDING.normalize("Øresund"), "oresund");
+        assert_eq!(PLAIN.normalize("Société"), "socie\u{301}te\u{301}");
+        assert_eq!(PLAIN.normalize("Łódź"), "ło\u{301}dz\u{301}");
+    }
+
+    #[test]
+    fn chars_agree_with_string_form() {
+        for text in ["ASCII only", "Straße", "Société Générale"] {
+            for normalizer in [PLAIN, FOLDING] {
+                let chars: String = normalizer.normalize_chars(text).into_iter().collect();
+                assert_eq!(chars, normalizer.normalize(text));
+            }
+        }
+    }
+
+    #[test]
+    fn offsets_point_at_utf16_source_chars() {
+        // "ß" expands to two chars, "😀" is a surrogate pair
+        let (chars, offsets) = PLAIN.normalize_with_offsets("aß😀é");
+        assert_eq!(chars, vec!['a', 's', 's', '😀', 'e', '\u{301}']);
+        assert_eq!(offsets, vec![(0, 1), (1, 2), (1, 2), (2, 4), (4, 5), (4, 5)]);
+
+        let (chars, offsets) = FOLDING.normalize_with_offsets("é!");
+        assert_eq!(chars, vec!['e', '!']);
+        assert_eq!(offsets, vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn splits_terms_on_whitespace() {
+        assert_eq!(tokenize_query("  apple\tbanana  cherry "), vec!["apple", "banana", "cherry"]);
+        assert!(tokenize_query("   ").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_phrases_whole() {
+        assert_eq!(tokenize_query(r#"a "b c" d"#), vec!["a", "b c", "d"]);
+        assert_eq!(tokenize_query(r#"x"y z"w"#), vec![r#"x"y"#, r#"z"w"#]);
+        assert!(tokenize_query(r#""" """#).is_empty());
+    }
+
+    #[test]
+    fn unclosed_quote_runs_to_end() {
+        assert_eq!(tokenize_query(r#"a "b c"#), vec!["a", "b c"]);
+        assert_eq!(tokenize_query(r#"a ""#), vec!["a"]);
+    }
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(edit_distance(&chars("AAPL"), &chars("APPL")), 1);
+        assert_eq!(edit_distance(&chars("ab"), &chars("ba")), 1);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("same"), &chars("same")), 0);
+    }
+
+    #[test]
+    fn similarity_scores_against_the_default_threshold() {
+        // The grid's default fuzzy threshold
+        let threshold = 0.5;
+        let score = |term: &str, token: &str| similarity(&chars(term), &chars(token));
+
+        assert_eq!(score("soft", "microsoft"), 1.0);
+        assert!(score("APPL", "AAPL") > threshold);
+        assert!(score("microsoft", "micorsoft") > threshold);
+        assert!(score("apple", "zebra") < threshold);
+        assert_eq!(score("", "abc"), 0.0);
+        assert_eq!(score("abc", ""), 0.0);
+    }
+
+    #[test]
+    fn long_tokens_skip_edit_distance() {
+        // Past term * 2 + 8 chars only the bigram half counts, capping the score
+        let term = chars("abcd");
+        let token = chars("abxdabxdabxdabxdabxd");
+        assert!(token.len() > term.len() * 2 + 8);
+        assert_eq!(similarity(&term, &token), 0.5 * bigram_dice(&term, &token));
+        assert!(similarity(&term, &token) <= 0.5);
+    }
+
+    #[test]
+    fn merges_overlapping_and_adjacent_ranges() {
+        let mut ranges = vec![(5, 8), (0, 2), (2, 4), (6, 10), (12, 13)];
+        merge_ranges(&mut ranges);
+        assert_eq!(ranges, vec![(0, 4), (5, 10), (12, 13)]);
+
+        let mut nested = vec![(0, 10), (3, 4), (10, 11)];
+        merge_ranges(&mut nested);
+        assert_eq!(nested, vec![(0, 11)]);
+
+        let mut empty: Vec<(u32, u32)> = Vec::new();
+        merge_ranges(&mut empty);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+//! XLSX export of the view or selected rows (`exportXlsx`)
+//!
+//! A minimal single-sheet workbook: strings are written inline (no shared
+//! string table), numbers as numbers, and number columns with format
+//! "timestamp"/"datetime"/"date" as Excel dates from epoch milliseconds.
+//! Nulls are left as empty cells. Parts are stored uncompressed in the zip -
+//! larger files, but no deflate code in the WASM binary.
+
+use crate::error::{ErrorCode, GridError};
+use crate::{ColumnData, GridStore};
+use js_sys::Reflect;
+use std::fmt::Write;
+use wasm_bindgen::prelude::*;
+
+/// Excel's sheet limits, header row included
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+/// Excel serial date of 1970-01-01
+const UNIX_EPOCH_SERIAL: f64 = 25_569.0;
+const DAY_MS: f64 = 86_400_000.0;
+
+// Cell styles, indices into cellXfs in styles.xml
+const STYLE_HEADER: u32 = 1;
+const STYLE_DATETIME: u32 = 2;
+const STYLE_DATE: u32 = 3;
+
+#[wasm_bindgen]
+impl GridStore {
+    /// Export rows as an .xlsx workbook - O(rows * cols)
+    /// Options: `{ rows?, columns?, headers?, includeHeaders = true,
+    /// sheetName = "Sheet1" }` - rows, columns and headers as in exportCsv.
+    /// Returns the file bytes, ready for a Blob download.
+    #[wasm_bindgen(js_name = exportXlsx)]
+    pub fn export_xlsx(&mut self, options: &JsValue) -> Result<Vec<u8>, GridError> {
+        let option = |key: &str| {
+            if options.is_object() {
+                Reflect::get(options, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+            } else {
+                JsValue::UNDEFINED
+            }
+        };
+
+        let columns = self.export_columns(&option("columns"))?;
+        let include_headers = option("includeHeaders");
+        let include_headers = include_headers.is_undefined() || include_headers.is_truthy();
+        let headers = option("headers");
+        let sheet_name = sheet_name(option("sheetName").as_string().as_deref().unwrap_or("Sheet1"));
+
+        let selected = self.export_rows(&option("rows"))?;
+        let rows: &[u32] = match &selected {
+            Some(rows) => rows,
+            None => {
+                self.ensure_view();
+                self.view.cached_view.as_deref().unwrap_or_default()
+            }
+        };
+        let sheet_rows = rows.len() + include_headers as usize;
+        if sheet_rows > MAX_ROWS || columns.len() > MAX_COLUMNS {
+            return Err(GridError::new(
+                ErrorCode::TooManyRows,
+                format!(
+                    "{} rows x {} columns exceeds Excel's {} x {} sheet limit",
+                    sheet_rows, columns.len(), MAX_ROWS, MAX_COLUMNS
+                ),
+            ));
+        }
+
+        let letters: Vec<String> = (0..columns.len()).map(column_letters).collect();
+        let styles: Vec<u32> = columns
+            .iter()
+            .map(|&col_idx| match self.columns[col_idx].format.as_deref() {
+                Some("timestamp" | "datetime") => STYLE_DATETIME,
+                Some("date") => STYLE_DATE,
+                _ => 0,
+            })
+            .collect();
+
+        let mut sheet = String::from(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+        ));
+        let mut row_num = 0;
+        if include_headers {
+            row_num += 1;
+            write!(sheet, r#"<row r="{}">"#, row_num).unwrap();
+            for (i, &col_idx) in columns.iter().enumerate() {
+                let header = self.export_header(&headers, col_idx);
+                push_string_cell(&mut sheet, &letters[i], row_num, &header, STYLE_HEADER);
+            }
+            sheet.push_str("</row>");
+        }
+        for &row in rows {
+            row_num += 1;
+            write!(sheet, r#"<row r="{}">"#, row_num).unwrap();
+            for (i, &col_idx) in columns.iter().enumerate() {
+                match &self.columns[col_idx].data {
+                    ColumnData::Strings(v) => {
+                        let s = &v[row as usize];
+                        if !s.is_empty() {
+                            push_string_cell(&mut sheet, &letters[i], row_num, s, 0);
+                        }
+                    }
+                    ColumnData::Numbers(v) => {
+                        let n = v[row as usize];
+                        if !n.is_finite() {
+                            continue;
+                        }
+                        match styles[i] {
+                            0 => write!(sheet, r#"<c r="{}{}"><v>{}</v></c>"#, letters[i], row_num, n),
+                            style => write!(
+                                sheet,
+                                r#"<c r="{}{}" s="{}"><v>{}</v></c>"#,
+                                letters[i], row_num, style, n / DAY_MS + UNIX_EPOCH_SERIAL
+                            ),
+                        }
+                        .unwrap();
+                    }
+                }
+            }
+            sheet.push_str("</row>");
+        }
+        sheet.push_str("</sheetData></worksheet>");
+
+        let workbook = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+                r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+                r#"<sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+            ),
+            xml_escape(&sheet_name)
+        );
+
+        let mut zip = ZipWriter::default();
+        zip.add("[Content_Types].xml", CONTENT_TYPES.as_bytes());
+        zip.add("_rels/.rels", ROOT_RELS.as_bytes());
+        zip.add("xl/workbook.xml", workbook.as_bytes());
+        zip.add("xl/_rels/workbook.xml.rels", WORKBOOK_RELS.as_bytes());
+        zip.add("xl/styles.xml", STYLES.as_bytes());
+        zip.add("xl/worksheets/sheet1.xml", sheet.as_bytes());
+        Ok(zip.finish())
+    }
+}
+
+/// Inline string cell - no shared string table to build
+fn push_string_cell(sheet: &mut String, letters: &str, row_num: usize, value: &str, style: u32) {
+    write!(sheet, r#"<c r="{}{}" t="inlineStr""#, letters, row_num).unwrap();
+    if style != 0 {
+        write!(sheet, r#" s="{}""#, style).unwrap();
+    }
+    sheet.push_str(r#"><is><t xml:space="preserve">"#);
+    sheet.push_str(&xml_escape(value));
+    sheet.push_str("</t></is></c>");
+}
+
+/// 0 -> "A", 25 -> "Z", 26 -> "AA"
+fn column_letters(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap()
+}
+
+/// Excel rejects sheet names over 31 chars or containing []:*?/\
+fn sheet_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    if name.trim().is_empty() { "Sheet1".to_string() } else { name }
+}
+
+/// Escape markup and drop chars XML 1.0 can't represent at all
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' || c == '\u{fffe}' || c == '\u{ffff}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Package parts
+// ============================================================================
+
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+    r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    r#"</Types>"#,
+);
+
+const ROOT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    r#"</Relationships>"#,
+);
+
+const WORKBOOK_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>"#,
+    r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+    r#"</Relationships>"#,
+);
+
+// cellXfs: 0 default, 1 bold header, 2 date + time, 3 date
+const STYLES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    r#"<numFmts count="2"><numFmt numFmtId="164" formatCode="yyyy-mm-dd hh:mm:ss"/>"#,
+    r#"<numFmt numFmtId="165" formatCode="yyyy-mm-dd"/></numFmts>"#,
+    r#"<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font>"#,
+    r#"<font><b/><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+    r#"<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>"#,
+    r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+    r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+    r#"<cellXfs count="4"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+    r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>"#,
+    r#"<xf numFmtId="164" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>"#,
+    r#"<xf numFmtId="165" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/></cellXfs>"#,
+    r#"<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>"#,
+    r#"</styleSheet>"#,
+);
+
+// ============================================================================
+// Zip container (stored entries only)
+// ============================================================================
+
+/// DOS date of 1980-01-01, the earliest a zip entry can carry
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+#[derive(Default)]
+struct ZipWriter {
+    out: Vec<u8>,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+
+        // Local file header
+        self.out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // flags
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.out.extend_from_slice(&DOS_DATE.to_le_bytes());
+        self.out.extend_from_slice(&crc.to_le_bytes());
+        self.out.extend_from_slice(&size.to_le_bytes()); // compressed
+        self.out.extend_from_slice(&size.to_le_bytes()); // uncompressed
+        self.out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(data);
+
+        // Central directory entry
+        self.central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // flags
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // method
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.central.extend_from_slice(&DOS_DATE.to_le_bytes());
+        self.central.extend_from_slice(&crc.to_le_bytes());
+        self.central.extend_from_slice(&size.to_le_bytes());
+        self.central.extend_from_slice(&size.to_le_bytes());
+        self.central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.central.extend_from_slice(&[0; 12]); // extra, comment, disk, internal + external attributes
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let central_offset = self.out.len() as u32;
+        let central_size = self.central.len() as u32;
+        self.out.append(&mut self.central);
+
+        // End of central directory
+        self.out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&[0; 4]); // disk numbers
+        self.out.extend_from_slice(&self.entries.to_le_bytes());
+        self.out.extend_from_slice(&self.entries.to_le_bytes());
+        self.out.extend_from_slice(&central_size.to_le_bytes());
+        self.out.extend_from_slice(&central_offset.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.out
+    }
+}
+
+/// CRC-32 (IEEE), as zip requires
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !data.iter().fold(!0u32, |crc, &b| TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_reference_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414f_a339);
+    }
+
+    #[test]
+    fn column_letters_are_bijective_base_26() {
+        assert_eq!(column_letters(0), "A");
+        assert_eq!(column_letters(25), "Z");
+        assert_eq!(column_letters(26), "AA");
+        assert_eq!(column_letters(51), "AZ");
+        assert_eq!(column_letters(52), "BA");
+        assert_eq!(column_letters(701), "ZZ");
+        assert_eq!(column_letters(702), "AAA");
+        assert_eq!(column_letters(16_383), "XFD");
+    }
+
+    #[test]
+    fn sheet_names_follow_excel_rules() {
+        assert_eq!(sheet_name("Q1 [draft]: a/b"), "Q1 draft ab");
+        assert_eq!(sheet_name(&"x".repeat(40)).len(), 31);
+        assert_eq!(sheet_name("[]"), "Sheet1");
+    }
+
+    #[test]
+    fn escapes_xml_and_drops_invalid_chars() {
+        assert_eq!(xml_escape("a<b & \"c\">"), "a&lt;b &amp; &quot;c&quot;&gt;");
+        assert_eq!(xml_escape("tab\there\u{1}\u{ffff}"), "tab\there");
+    }
+
+    #[test]
+    fn zip_lists_every_entry() {
+        let mut zip = ZipWriter::default();
+        zip.add("a.xml", b"<a/>");
+        zip.add("b.xml", b"");
+        let bytes = zip.finish();
+
+        assert_eq!(&bytes[..4], &0x0403_4b50u32.to_le_bytes());
+        let eocd = &bytes[bytes.len() - 22..];
+        assert_eq!(&eocd[..4], &0x0605_4b50u32.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([eocd[10], eocd[11]]), 2);
+    }
+}